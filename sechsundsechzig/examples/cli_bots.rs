@@ -0,0 +1,34 @@
+use sechsundsechzig::{game::SechsUndSechzig, rules::Rules, sampler::Sampler, variant::Variant};
+use tbsux::{
+    cli::{run_cli_seated, Seat},
+    ismcts::Ismcts,
+    mcts::{Budget, MctsConfig},
+};
+
+/// Human plays as player 0 against computer players, `two` or `three` argument selects variant
+/// and `--rules RULES` rules, read by `Rules::from_args`
+fn main() {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    let variant = args
+        .iter()
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(Variant::FourPlayers);
+    let rules = match Rules::from_args(&args, variant) {
+        Ok(rules) => rules,
+        Err(err) => return println!("{}", err),
+    };
+    let config = MctsConfig {
+        budget: Budget::Iterations(300),
+        ..Default::default()
+    };
+    let seats = (0..variant.number_of_players())
+        .map(|player| match player {
+            0 => Seat::Human,
+            _ => Seat::Bot(Box::new(Ismcts::with_random_seed(config, Sampler))),
+        })
+        .collect();
+    let game = SechsUndSechzig::with_random_seed(variant).with_rules(rules);
+    if let Err(err) = run_cli_seated(game, seats) {
+        println!("Game interrupted: {}", err);
+    }
+}
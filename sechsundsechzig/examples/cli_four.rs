@@ -1,7 +1,13 @@
-use sechsundsechzig::{game::SechsUndSechzig, variant::Variant};
+use sechsundsechzig::{game::SechsUndSechzig, rules::Rules, variant::Variant};
 use tbsux::cli::run_cli;
 
+/// Game of four players in terminal, `--rules RULES` selects rules, read by `Rules::from_args`
 fn main() {
-    let game = SechsUndSechzig::with_random_seed(Variant::FourPlayers);
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    let rules = match Rules::from_args(&args, Variant::FourPlayers) {
+        Ok(rules) => rules,
+        Err(err) => return println!("{}", err),
+    };
+    let game = SechsUndSechzig::with_random_seed(Variant::FourPlayers).with_rules(rules);
     run_cli(game);
 }
@@ -0,0 +1,28 @@
+use sechsundsechzig::{game::SechsUndSechzig, sampler::Sampler, variant::Variant};
+use tbsux::{
+    agent::{run_agents, Agent, RandomAgent},
+    ismcts::Ismcts,
+    mcts::{Budget, MctsConfig},
+};
+
+fn main() {
+    let game = SechsUndSechzig::with_random_seed(Variant::FourPlayers);
+    let config = MctsConfig {
+        budget: Budget::Iterations(100),
+        ..Default::default()
+    };
+    // team 0 (players 0 and 2) is played by ISMCTS, team 1 plays randomly
+    let mut agents: Vec<Box<dyn Agent<SechsUndSechzig>>> = (0..4)
+        .map(|player| {
+            if player % 2 == 0 {
+                Box::new(Ismcts::with_random_seed(config, Sampler)) as Box<dyn Agent<_>>
+            } else {
+                Box::new(RandomAgent::with_random_seed())
+            }
+        })
+        .collect();
+    match run_agents(&game, &mut agents) {
+        Ok(score) => println!("Game finished, result:\n{}", score),
+        Err(err) => println!("Game interrupted: {}", err),
+    }
+}
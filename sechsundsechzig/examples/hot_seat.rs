@@ -0,0 +1,52 @@
+use sechsundsechzig::{game::SechsUndSechzig, rules::Rules, save, variant::Variant};
+use tbsux::{
+    cli::{run_cli_from, CliOptions, Seat, UndoPolicy},
+    prelude::*,
+};
+
+/// All players share one terminal, `two` or `three` argument selects variant
+/// and `--rules RULES` rules, read by `Rules::from_args`
+///
+/// Game can be saved with `:save PATH` and resumed with `--load PATH` argument.
+/// Moves can be taken back with `:undo`, with `--strict-undo` only when all players agree.
+fn main() {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    let state = match args.iter().position(|arg| arg == "--load") {
+        Some(index) => match args.get(index + 1).map(save::load_from_file) {
+            Some(Ok(state)) => state,
+            Some(Err(err)) => return println!("Could not load game: {}", err),
+            None => return println!("Missing path after --load"),
+        },
+        None => {
+            let variant = args
+                .iter()
+                .find_map(|arg| arg.parse().ok())
+                .unwrap_or(Variant::FourPlayers);
+            let rules = match Rules::from_args(&args, variant) {
+                Ok(rules) => rules,
+                Err(err) => return println!("{}", err),
+            };
+            SechsUndSechzig::with_random_seed(variant)
+                .with_rules(rules)
+                .initial_state()
+        }
+    };
+
+    let seats = (0..state.variant().number_of_players())
+        .map(|_| Seat::Human)
+        .collect();
+    let options = CliOptions {
+        hot_seat: true,
+        save: Some(Box::new(|state, path| {
+            save::save_to_file(state, path).map_err(|err| err.into())
+        })),
+        undo: if args.iter().any(|arg| arg == "--strict-undo") {
+            UndoPolicy::Unanimous
+        } else {
+            UndoPolicy::Allowed
+        },
+    };
+    if let Err(err) = run_cli_from::<SechsUndSechzig>(state, seats, options) {
+        println!("Game interrupted: {}", err);
+    }
+}
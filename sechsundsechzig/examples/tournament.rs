@@ -0,0 +1,42 @@
+use sechsundsechzig::{game::SechsUndSechzig, sampler::Sampler, variant::Variant};
+use tbsux::{
+    agent::{Agent, RandomAgent},
+    ismcts::Ismcts,
+    mcts::{Budget, MctsConfig},
+    tournament::{run_tournament, Entrant, TournamentConfig},
+};
+
+fn ismcts(iterations: u32) -> impl Fn(u64) -> Box<dyn Agent<SechsUndSechzig>> {
+    move |seed| {
+        let config = MctsConfig {
+            budget: Budget::Iterations(iterations),
+            ..Default::default()
+        };
+        Box::new(Ismcts::with_seed(config, Sampler, seed))
+    }
+}
+
+/// Rates random and ISMCTS bots against each other, `four` argument selects four players variant
+fn main() {
+    let variant = match std::env::args().nth(1).as_deref() {
+        Some("four") => Variant::FourPlayers,
+        _ => Variant::ThreePlayers,
+    };
+    let entrants = vec![
+        Entrant::new("random", |seed| Box::new(RandomAgent::with_seed(seed))),
+        Entrant::new("ismcts-30", ismcts(30)),
+        Entrant::new("ismcts-100", ismcts(100)),
+    ];
+    let config = TournamentConfig {
+        games_per_lineup: 2,
+        ..Default::default()
+    };
+    match run_tournament(
+        |seed| SechsUndSechzig::with_seed(variant, seed),
+        &entrants,
+        config,
+    ) {
+        Ok(report) => print!("{}", report),
+        Err(err) => println!("Tournament interrupted: {}", err),
+    }
+}
@@ -0,0 +1,594 @@
+//! JSON over HTTP interface for hosting many games at once
+//!
+//! - `POST /games` with `{"variant": "three_players"}` (and optional `seed` and
+//!   `rules`, naming one of `Rules::PRESETS`) creates a game, returning its `id`
+//!   and one secret token per seat
+//! - `GET /games/{id}` returns score, results of every finished round with
+//!   points scored by its winners, and player to move
+//! - `GET /games/{id}/view?token={token}` returns view of seat owning the
+//!   token, with legal moves when it is that player's turn
+//! - `POST /games/{id}/moves` with `{"token": ..., "move": "ask-about ♥"}`
+//!   plays a move written in the same syntax as in terminal
+//!
+//! Errors are returned as `{"error": ...}` with matching status code. Games are
+//! kept in memory, and optionally written to a snapshot file after every change,
+//! so they survive restarts.
+
+use std::{
+    collections::HashMap,
+    error, fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use tbsux::{
+    playered::{Player, View},
+    prelude::*,
+};
+
+use crate::{
+    error::SechsUndSechzigError,
+    game::{SechsUndSechzig, SechsUndSechzigPlayerView, SechsUndSechzigState},
+    round::FinishedRound,
+    rules::Rules,
+    sus_move::SusMove,
+    team::Team,
+    variant::Variant,
+};
+
+#[derive(Debug)]
+pub enum ApiError {
+    NotFound,
+    BadRequest(String),
+    InvalidToken,
+    IllegalMove(SechsUndSechzigError),
+    Snapshot(io::Error),
+}
+
+impl ApiError {
+    pub fn status(&self) -> u16 {
+        use ApiError::*;
+        match self {
+            NotFound => 404,
+            BadRequest(_) => 400,
+            InvalidToken => 403,
+            IllegalMove(_) => 409,
+            Snapshot(_) => 500,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl error::Error for ApiError {}
+
+#[derive(Deserialize)]
+struct NewGame {
+    variant: Variant,
+    seed: Option<u64>,
+    rules: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MoveRequest {
+    token: String,
+    #[serde(rename = "move")]
+    mv: String,
+}
+
+#[derive(Serialize)]
+struct Created<'a> {
+    id: &'a str,
+    tokens: &'a [String],
+}
+
+#[derive(Serialize)]
+struct TeamPoints {
+    team: u32,
+    players: Vec<Player>,
+    points: i32,
+}
+
+#[derive(Serialize)]
+struct Summary<'a> {
+    id: &'a str,
+    variant: Variant,
+    current_player: Option<Player>,
+    winner: Option<u32>,
+    score: Vec<TeamPoints>,
+    rounds: Vec<RoundPoints<'a>>,
+}
+
+#[derive(Serialize)]
+struct RoundPoints<'a> {
+    #[serde(flatten)]
+    result: &'a FinishedRound,
+    /// Points scored by every winner
+    points: i32,
+}
+
+#[derive(Serialize)]
+struct SeatView<'a> {
+    player: Player,
+    current_player: Option<Player>,
+    legal_moves: Vec<String>,
+    view: &'a SechsUndSechzigPlayerView,
+}
+
+/// What is written to snapshot - game is restored by replaying its moves
+#[derive(Serialize, Deserialize)]
+struct StoredGame {
+    id: String,
+    variant: Variant,
+    seed: u64,
+    /// Missing in snapshots written before rules could be chosen, default ones are used then
+    #[serde(default)]
+    rules: Option<Rules>,
+    tokens: Vec<String>,
+    moves: Vec<String>,
+}
+
+struct Entry {
+    state: SechsUndSechzigState,
+    tokens: Vec<String>,
+}
+
+impl Entry {
+    fn new(state: SechsUndSechzigState, tokens: Vec<String>) -> Entry {
+        Entry { state, tokens }
+    }
+
+    fn play(&mut self, mv: SusMove) -> Result<(), SechsUndSechzigError> {
+        self.state = self.state.move_reducer(mv)?;
+        Ok(())
+    }
+
+    fn current_player(&self) -> Option<Player> {
+        match self.state.progress_report() {
+            ProgressReport::InProgress(view) => Some(view.current_player()),
+            ProgressReport::Finished(_) => None,
+        }
+    }
+}
+
+fn team_points(state: &SechsUndSechzigState) -> Vec<TeamPoints> {
+    let score = state.score();
+    Team::teams(state.variant())
+        .map(|team| TeamPoints {
+            team: team.0,
+            players: team.players().map(Iterator::collect).unwrap_or_default(),
+            points: score.team_points(&team).unwrap_or(0),
+        })
+        .collect()
+}
+
+/// Games hosted by the service, handling requests independently of HTTP server
+pub struct Api {
+    games: HashMap<String, Entry>,
+    snapshot: Option<PathBuf>,
+    rng: StdRng,
+}
+
+impl Api {
+    pub fn new() -> Api {
+        Api {
+            games: HashMap::new(),
+            snapshot: None,
+            rng: SeedableRng::from_entropy(),
+        }
+    }
+
+    /// Keeps games in snapshot file at `path`, loading ones already stored there
+    pub fn with_snapshot(path: impl AsRef<Path>) -> Result<Api, Box<dyn error::Error>> {
+        let path = path.as_ref().to_owned();
+        let mut api = Api::new();
+        if path.exists() {
+            let stored: Vec<StoredGame> = serde_json::from_str(&fs::read_to_string(&path)?)?;
+            for game in stored {
+                let rules = game
+                    .rules
+                    .unwrap_or_else(|| Rules::for_variant(game.variant));
+                let initial = SechsUndSechzig::with_seed(game.variant, game.seed)
+                    .with_rules(rules)
+                    .initial_state();
+                let mut entry = Entry::new(initial, game.tokens);
+                for mv in game.moves {
+                    entry.play(mv.parse()?)?;
+                }
+                api.games.insert(game.id, entry);
+            }
+        }
+        api.snapshot = Some(path);
+        Ok(api)
+    }
+
+    /// Answers request with status code and JSON body
+    pub fn handle(&mut self, method: &str, url: &str, body: &str) -> (u16, String) {
+        let result = self.route(method, url, body);
+        let result = result.and_then(|response| {
+            if method == "POST" {
+                self.write_snapshot().map_err(ApiError::Snapshot)?;
+            }
+            Ok(response)
+        });
+        match result {
+            Ok(response) => (200, response),
+            Err(err) => {
+                let message = match &err {
+                    ApiError::BadRequest(message) => message.clone(),
+                    err => err.to_string(),
+                };
+                (
+                    err.status(),
+                    serde_json::json!({ "error": message }).to_string(),
+                )
+            }
+        }
+    }
+
+    fn route(&mut self, method: &str, url: &str, body: &str) -> Result<String, ApiError> {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let segments: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+        match (method, segments.as_slice()) {
+            ("POST", ["games"]) => self.create(parse(body)?),
+            ("GET", ["games", id]) => self.summary(id),
+            ("GET", ["games", id, "view"]) => {
+                let token = query
+                    .split('&')
+                    .find_map(|pair| pair.strip_prefix("token="))
+                    .ok_or(ApiError::InvalidToken)?;
+                self.view(id, token)
+            }
+            ("POST", ["games", id, "moves"]) => self.play(id, parse(body)?),
+            _ => Err(ApiError::NotFound),
+        }
+    }
+
+    fn create(&mut self, request: NewGame) -> Result<String, ApiError> {
+        let rules = match &request.rules {
+            Some(name) => Rules::preset(name)
+                .ok_or_else(|| ApiError::BadRequest(format!("unknown rules {}", name)))?,
+            None => Rules::for_variant(request.variant),
+        };
+        let seed = request.seed.unwrap_or_else(|| self.rng.next_u64());
+        let id = format!("{:016x}", self.rng.next_u64());
+        let tokens: Vec<_> = (0..request.variant.number_of_players())
+            .map(|_| format!("{:016x}", self.rng.next_u64()))
+            .collect();
+        let state = SechsUndSechzig::with_seed(request.variant, seed)
+            .with_rules(rules)
+            .initial_state();
+        let response = to_json(&Created {
+            id: &id,
+            tokens: &tokens,
+        });
+        self.games.insert(id, Entry::new(state, tokens));
+        Ok(response)
+    }
+
+    fn summary(&self, id: &str) -> Result<String, ApiError> {
+        let entry = self.games.get(id).ok_or(ApiError::NotFound)?;
+        let score = entry.state.score();
+        Ok(to_json(&Summary {
+            id,
+            variant: entry.state.variant(),
+            current_player: entry.current_player(),
+            winner: score.winner().map(|team| team.0),
+            score: team_points(&entry.state),
+            rounds: entry
+                .state
+                .results()
+                .iter()
+                .map(|result| RoundPoints {
+                    result,
+                    points: result.points(),
+                })
+                .collect(),
+        }))
+    }
+
+    fn view(&self, id: &str, token: &str) -> Result<String, ApiError> {
+        let entry = self.games.get(id).ok_or(ApiError::NotFound)?;
+        let player = seat(entry, token)?;
+        let current_player = entry.current_player();
+        let view = entry.state.player_view(player);
+        let legal_moves = if current_player == Some(player) {
+            entry
+                .state
+                .legal_moves()
+                .iter()
+                .map(|mv| mv.to_string())
+                .collect()
+        } else {
+            vec![]
+        };
+        Ok(to_json(&SeatView {
+            player,
+            current_player,
+            legal_moves,
+            view: &view,
+        }))
+    }
+
+    fn play(&mut self, id: &str, request: MoveRequest) -> Result<String, ApiError> {
+        let entry = self.games.get_mut(id).ok_or(ApiError::NotFound)?;
+        let player = seat(entry, &request.token)?;
+        if entry.current_player() != Some(player) {
+            return Err(ApiError::BadRequest("not your turn".to_owned()));
+        }
+        let mv: SusMove = request
+            .mv
+            .parse()
+            .map_err(|_| ApiError::BadRequest("could not parse move".to_owned()))?;
+        entry.play(mv).map_err(ApiError::IllegalMove)?;
+        self.summary(id)
+    }
+
+    fn write_snapshot(&self) -> io::Result<()> {
+        let path = match &self.snapshot {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let stored: Vec<_> = self
+            .games
+            .iter()
+            .map(|(id, entry)| StoredGame {
+                id: id.clone(),
+                variant: entry.state.variant(),
+                seed: entry.state.seed(),
+                rules: Some(*entry.state.rules()),
+                tokens: entry.tokens.clone(),
+                moves: entry
+                    .state
+                    .history()
+                    .into_iter()
+                    .map(|mv| mv.to_string())
+                    .collect(),
+            })
+            .collect();
+        // written next to snapshot first, so a crash never leaves it half written
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, to_json(&stored))?;
+        fs::rename(temporary, path)
+    }
+}
+
+impl Default for Api {
+    fn default() -> Api {
+        Api::new()
+    }
+}
+
+fn seat(entry: &Entry, token: &str) -> Result<Player, ApiError> {
+    entry
+        .tokens
+        .iter()
+        .position(|t| t == token)
+        .map(|player| player as Player)
+        .ok_or(ApiError::InvalidToken)
+}
+
+fn parse<'a, T: Deserialize<'a>>(body: &'a str) -> Result<T, ApiError> {
+    serde_json::from_str(body).map_err(|err| ApiError::BadRequest(err.to_string()))
+}
+
+fn to_json(value: &impl Serialize) -> String {
+    serde_json::to_string(value).expect("Responses are always serializable")
+}
+
+/// Answers requests coming to `server` until it is unblocked
+pub fn serve_http(server: &tiny_http::Server, api: &mut Api) {
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let (status, response) = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => api.handle(request.method().as_str(), request.url(), &body),
+            Err(_) => (400, r#"{"error":"body is not valid UTF-8"}"#.to_owned()),
+        };
+        let header = tiny_http::Header::from_bytes("Content-Type", "application/json")
+            .expect("Header is valid");
+        let response = tiny_http::Response::from_string(response)
+            .with_status_code(status)
+            .with_header(header);
+        // client which went away does not need an answer
+        let _ = request.respond(response);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpStream,
+        sync::Arc,
+        thread,
+    };
+
+    use serde_json::Value;
+
+    use super::*;
+
+    fn json(response: (u16, String)) -> (u16, Value) {
+        (response.0, serde_json::from_str(&response.1).unwrap())
+    }
+
+    fn create(api: &mut Api) -> (String, Vec<String>) {
+        let (status, created) = json(api.handle(
+            "POST",
+            "/games",
+            r#"{"variant": "three_players", "seed": 5}"#,
+        ));
+        assert_eq!(200, status);
+        let tokens = created["tokens"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|token| token.as_str().unwrap().to_owned())
+            .collect();
+        (created["id"].as_str().unwrap().to_owned(), tokens)
+    }
+
+    fn play_first_move(api: &mut Api, id: &str, tokens: &[String]) -> (u16, Value) {
+        let (_, summary) = json(api.handle("GET", &format!("/games/{}", id), ""));
+        let token = &tokens[summary["current_player"].as_u64().unwrap() as usize];
+        let (_, view) = json(api.handle("GET", &format!("/games/{}/view?token={}", id, token), ""));
+        let mv = view["legal_moves"][0].as_str().unwrap();
+        let body = serde_json::json!({ "token": token, "move": mv }).to_string();
+        json(api.handle("POST", &format!("/games/{}/moves", id), &body))
+    }
+
+    #[test]
+    fn api_can_be_moved_to_server_thread() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Api>();
+    }
+
+    #[test]
+    fn reports_points_of_finished_rounds() {
+        let mut api = Api::new();
+        let (id, tokens) = create(&mut api);
+        assert_eq!(3, tokens.len());
+
+        let mut summary = Value::Null;
+        while summary["rounds"].as_array().is_none_or(Vec::is_empty) {
+            let (status, response) = play_first_move(&mut api, &id, &tokens);
+            assert_eq!(200, status);
+            summary = response;
+        }
+        let round = &summary["rounds"][0];
+        let winners = round["winners"].as_array().unwrap();
+        for team in summary["score"].as_array().unwrap() {
+            let won = team["players"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .all(|player| winners.contains(player));
+            let expected = if won {
+                round["points"].as_i64().unwrap()
+            } else {
+                0
+            };
+            assert_eq!(expected, team["points"].as_i64().unwrap());
+        }
+        assert!(round["points"].as_i64().unwrap() > 0);
+    }
+
+    #[test]
+    fn view_shows_only_own_hand_and_moves_on_own_turn() {
+        let mut api = Api::new();
+        let (id, tokens) = create(&mut api);
+        let (_, summary) = json(api.handle("GET", &format!("/games/{}", id), ""));
+        let current = summary["current_player"].as_u64().unwrap() as usize;
+
+        for (player, token) in tokens.iter().enumerate() {
+            let (status, view) =
+                json(api.handle("GET", &format!("/games/{}/view?token={}", id, token), ""));
+            assert_eq!(200, status);
+            assert_eq!(player as u64, view["player"].as_u64().unwrap());
+            assert_eq!(4, view["view"]["hand"].as_array().unwrap().len());
+            let moves = view["legal_moves"].as_array().unwrap();
+            assert_eq!(player == current, !moves.is_empty());
+        }
+    }
+
+    #[test]
+    fn rejects_bad_requests() {
+        let mut api = Api::new();
+        let (id, tokens) = create(&mut api);
+        let (_, summary) = json(api.handle("GET", &format!("/games/{}", id), ""));
+        let current = summary["current_player"].as_u64().unwrap() as usize;
+        let waiting = &tokens[(current + 1) % tokens.len()];
+        let moves = format!("/games/{}/moves", id);
+        let play =
+            |token: &str, mv: &str| serde_json::json!({ "token": token, "move": mv }).to_string();
+
+        assert_eq!(404, api.handle("GET", "/games/missing", "").0);
+        assert_eq!(400, api.handle("POST", "/games", "{}").0);
+        assert_eq!(403, api.handle("POST", &moves, &play("wrong", "pass")).0);
+        assert_eq!(400, api.handle("POST", &moves, &play(waiting, "pass")).0);
+        assert_eq!(
+            400,
+            api.handle("POST", &moves, &play(&tokens[current], "jump"))
+                .0
+        );
+        assert_eq!(
+            409,
+            api.handle("POST", &moves, &play(&tokens[current], "A ♠")).0
+        );
+    }
+
+    #[test]
+    fn restores_games_from_snapshot() {
+        let path = std::env::temp_dir().join(format!("sus-api-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut api = Api::with_snapshot(&path).unwrap();
+        let (id, tokens) = create(&mut api);
+        for _ in 0..30 {
+            play_first_move(&mut api, &id, &tokens);
+        }
+        let summary = api.handle("GET", &format!("/games/{}", id), "");
+
+        let mut restored = Api::with_snapshot(&path).unwrap();
+        assert_eq!(
+            summary,
+            restored.handle("GET", &format!("/games/{}", id), "")
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn snapshot_keeps_rules_of_game() {
+        let path = std::env::temp_dir().join(format!("sus-api-rules-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut api = Api::with_snapshot(&path).unwrap();
+        let body = r#"{"variant": "three_players", "seed": 5, "rules": "silesian"}"#;
+        let (_, created) = json(api.handle("POST", "/games", body));
+        let id = created["id"].as_str().unwrap();
+        assert_eq!(
+            400,
+            api.handle(
+                "POST",
+                "/games",
+                r#"{"variant": "three_players", "rules": "cracow"}"#
+            )
+            .0
+        );
+
+        let restored = Api::with_snapshot(&path).unwrap();
+        assert_eq!(Rules::silesian(), *restored.games[id].state.rules());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn serves_requests_over_localhost() {
+        let server = Arc::new(tiny_http::Server::http("127.0.0.1:0").unwrap());
+        let address = server.server_addr().to_ip().unwrap();
+        let handle = {
+            let server = server.clone();
+            thread::spawn(move || serve_http(&server, &mut Api::new()))
+        };
+
+        let body = r#"{"variant": "four_players"}"#;
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "POST /games HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        let created: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(4, created["tokens"].as_array().unwrap().len());
+
+        server.unblock();
+        handle.join().unwrap();
+    }
+}
@@ -1,18 +1,37 @@
 use tbsux::playered::Player;
 
 use crate::{
+    cards::Suit,
     contract::{Contract, GameType},
     error::{SechsUndSechzigError, SusResult},
+    rules::Rules,
     variant::Variant,
 };
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Bid {
     Pass,
     Raise,
     Game(GameType),
 }
 
+impl Bid {
+    /// Every bid that can be made, regardless of whether it is vaild in current contract
+    pub fn all() -> impl Iterator<Item = Bid> {
+        use Bid::*;
+        use GameType::*;
+
+        let suited = Suit::SUITS
+            .iter()
+            .flat_map(|suit| vec![AskingAbout(*suit), LookingFor(*suit)]);
+        vec![Pass, Raise]
+            .into_iter()
+            .chain(suited.chain(vec![Misery, Shower]).map(Game))
+    }
+}
+
 pub enum BidResult {
     Finish(Contract),
     Continue(Contract, Player),
@@ -24,12 +43,21 @@ pub fn bidding(
     bid: &Bid,
     player: Player,
     variant: &Variant,
-    initial_dealer: Player
+    initial_dealer: Player,
+    rules: &Rules,
 ) -> SusResult<BidResult> {
     use Bid::*;
     use BidResult::*;
     use GameType::*;
     let next = |player| variant.next_player(player);
+
+    // house rules may forbid some contracts and limit raises
+    match bid {
+        Game(Misery) if !rules.misery_allowed => return Err(SechsUndSechzigError::InvaildBid),
+        Game(Shower) if !rules.shower_allowed => return Err(SechsUndSechzigError::InvaildBid),
+        Raise if !rules.can_raise(current_contract.multiplier) => return Err(SechsUndSechzigError::InvaildBid),
+        _ => (),
+    }
     
     match (current_contract, bid, player) {
         // === STAGE 1 - with four cards ===
@@ -93,7 +121,7 @@ pub fn bidding(
         // player bids "misery" when misery or shower is not bidden yet - change contract to shower, make him dealer, reset multiplier
         // in 4-players game dealer's teammate cannot bid misery unless he is answering to raise
         (c, Game(Misery), player) if c.game_type != Shower && c.game_type != Misery 
-            && !(c.multiplier == 1 && c.dealers_teammate(&variant) == Some(player)) =>
+            && !(c.multiplier == 1 && c.dealers_teammate(variant) == Some(player)) =>
             Ok(Continue(Contract { game_type: Misery, dealer: player,  multiplier: 1 }, next(player))),
 
         // == UNIVERSAL MATCHES ==
@@ -125,7 +153,7 @@ mod tests {
     }
 
     impl BiddingTest {
-        fn _test(&self, debug: bool) {
+        fn _test(&self, rules: &Rules, debug: bool) {
             let initial_state = TestResult::InProgress {
                 contract: Contract::initial(self.initial_dealer),
                 player: self.initial_dealer,
@@ -143,7 +171,14 @@ mod tests {
                             )
                         };
                         assert_eq!(*expected_player, player, "Invaild player");
-                        match bidding(&contract, bid, player, &self.variant, self.initial_dealer) {
+                        match bidding(
+                            &contract,
+                            bid,
+                            player,
+                            &self.variant,
+                            self.initial_dealer,
+                            rules,
+                        ) {
                             Ok(BidResult::Continue(contract, player)) => {
                                 if debug {
                                     println!(", continuing...")
@@ -172,12 +207,25 @@ mod tests {
         }
 
         fn test(&self) {
-            self._test(false);
+            self._test(&Rules::default(), false);
+        }
+
+        fn test_with(&self, rules: &Rules) {
+            self._test(rules, false);
         }
 
         #[allow(dead_code)]
         fn debug(&self) {
-            self._test(true);
+            self._test(&Rules::default(), true);
+        }
+    }
+
+    #[test]
+    fn all_bids_are_distinct() {
+        let bids: Vec<_> = Bid::all().collect();
+        assert_eq!(12, bids.len());
+        for (i, bid) in bids.iter().enumerate() {
+            assert!(!bids[i + 1..].contains(bid));
         }
     }
 
@@ -282,4 +330,34 @@ mod tests {
         }
         .test()
     }
+
+    #[test]
+    fn raises_are_limited_by_rules() {
+        BiddingTest {
+            initial_dealer: 0,
+            variant: ThreePlayers,
+            moves: vec![(0, Raise), (1, Raise), (2, Raise)],
+            expected: Error {
+                error: SechsUndSechzigError::InvaildBid,
+            },
+        }
+        .test_with(&Rules::silesian())
+    }
+
+    #[test]
+    fn rules_can_forbid_shower() {
+        let rules = Rules {
+            shower_allowed: false,
+            ..Rules::default()
+        };
+        BiddingTest {
+            initial_dealer: 0,
+            variant: ThreePlayers,
+            moves: vec![(0, Game(AskingAbout(Suit::Heart))), (0, Game(Shower))],
+            expected: Error {
+                error: SechsUndSechzigError::InvaildBid,
+            },
+        }
+        .test_with(&rules)
+    }
 }
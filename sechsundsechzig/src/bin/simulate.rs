@@ -0,0 +1,123 @@
+use std::process::Command;
+
+use sechsundsechzig::{
+    game::{SechsUndSechzig, SechsUndSechzigState},
+    rules::Rules,
+    sampler::Sampler,
+    stats::Statistics,
+    variant::Variant,
+};
+use tbsux::{
+    agent::{Agent, RandomAgent},
+    engine::{EngineConfig, ExternalAgent},
+    ismcts::Ismcts,
+    mcts::{Budget, MctsConfig},
+    playered::View,
+    prelude::*,
+};
+
+/// Plays seeded games between bots and prints their statistics
+///
+/// Usage: `simulate [two|four] [--games N] [--seed SEED] [--bots BOT,...] [--rules RULES] [--csv]`,
+/// where every bot is `random`, `ismcts-ITERATIONS` or `engine:COMMAND` running
+/// external engine. Bots take seats in given order, repeated when there are
+/// fewer of them than players. Rules are read by `Rules::from_args`.
+fn main() {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    let option = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|index| args.get(index + 1))
+    };
+    let variant = args
+        .iter()
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(Variant::ThreePlayers);
+    let games: u64 = match option("--games").map(|games| games.parse()) {
+        Some(Ok(games)) => games,
+        Some(Err(_)) => return eprintln!("--games needs a number of games"),
+        None => 100,
+    };
+    let first_seed: u64 = match option("--seed").map(|seed| seed.parse()) {
+        Some(Ok(seed)) => seed,
+        Some(Err(_)) => return eprintln!("--seed needs a number"),
+        None => 0,
+    };
+    let rules = match Rules::from_args(&args, variant) {
+        Ok(rules) => rules,
+        Err(err) => return eprintln!("{}", err),
+    };
+    let bots: Vec<_> = option("--bots")
+        .map_or("random", String::as_str)
+        .split(',')
+        .collect();
+    let is_known = |bot: &&str| bot.starts_with("engine:") || bot_for_seat(bot, 0).is_some();
+    if let Some(unknown) = bots.iter().find(|bot| !is_known(bot)) {
+        return eprintln!(
+            "Unknown bot {}, use random, ismcts-ITERATIONS or engine:COMMAND",
+            unknown
+        );
+    }
+
+    let mut stats = Statistics::new(variant);
+    for seed in first_seed..first_seed + games {
+        let mut agents: Vec<Box<dyn Agent<SechsUndSechzig>>> = (0..variant.number_of_players()
+            as usize)
+            .map(|seat| bot_for_seat(bots[seat % bots.len()], seed))
+            .collect::<Option<_>>()
+            .unwrap_or_default();
+        if agents.is_empty() {
+            return;
+        }
+        let game = SechsUndSechzig::with_seed(variant, seed).with_rules(rules);
+        match play(&game, &mut agents) {
+            Some(finished) => stats.record(&finished),
+            None => return eprintln!("Bot gave up in game with seed {}", seed),
+        }
+    }
+
+    if args.iter().any(|arg| arg == "--csv") {
+        if let Err(err) = stats.write_csv(&mut std::io::stdout()) {
+            eprintln!("Could not write statistics: {}", err);
+        }
+    } else {
+        print!("{}", stats);
+    }
+}
+
+fn bot_for_seat(name: &str, seed: u64) -> Option<Box<dyn Agent<SechsUndSechzig>>> {
+    if let Some(command) = name.strip_prefix("engine:") {
+        let engine = ExternalAgent::spawn(Command::new(command), EngineConfig::default());
+        return match engine {
+            Ok(engine) => Some(Box::new(engine)),
+            Err(err) => {
+                eprintln!("Could not start engine {}: {}", command, err);
+                None
+            }
+        };
+    }
+    if name == "random" {
+        return Some(Box::new(RandomAgent::with_seed(seed)));
+    }
+    let iterations = name.strip_prefix("ismcts-")?.parse().ok()?;
+    let config = MctsConfig {
+        budget: Budget::Iterations(iterations),
+        ..Default::default()
+    };
+    Some(Box::new(Ismcts::with_seed(config, Sampler, seed)))
+}
+
+/// Plays game to the end, returning final state with its history
+fn play(
+    game: &SechsUndSechzig,
+    agents: &mut [Box<dyn Agent<SechsUndSechzig>>],
+) -> Option<SechsUndSechzigState> {
+    let mut state = game.initial_state();
+    while let ProgressReport::InProgress(view) = state.progress_report() {
+        let player = view.current_player();
+        let mv =
+            agents[player as usize].select_move(&view.player_view(player), &state.legal_moves())?;
+        state = state.move_reducer(mv).ok()?;
+    }
+    Some(state)
+}
@@ -0,0 +1,70 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    thread,
+};
+
+/// Joins a game hosted by `sus_server`, usage: `sus_client ADDRESS [SEAT]`
+///
+/// Lines typed on stdin are sent to server as they are.
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let address = match args.next() {
+        Some(address) => address,
+        None => return eprintln!("Usage: sus_client ADDRESS [SEAT]"),
+    };
+    let seat = args.next();
+
+    let mut stream = match TcpStream::connect(&address) {
+        Ok(stream) => stream,
+        Err(err) => return eprintln!("Could not connect to {}: {}", address, err),
+    };
+    let reader = BufReader::new(stream.try_clone().expect("Could not clone stream"));
+    let printer = thread::spawn(move || print_messages(reader));
+
+    let claim = match seat {
+        Some(seat) => format!("SEAT {}", seat),
+        None => "SEAT".to_owned(),
+    };
+    let sent = writeln!(stream, "{}", claim).and_then(|_| {
+        for line in io::stdin().lock().lines() {
+            writeln!(stream, "{}", line?)?;
+        }
+        Ok(())
+    });
+    if sent.is_ok() {
+        printer.join().expect("Printer thread panicked");
+    }
+}
+
+fn print_messages(reader: BufReader<TcpStream>) {
+    let mut seat = None;
+    for line in reader.lines().map_while(Result::ok) {
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("SEATED"), Some(player)) => {
+                seat = Some(player.to_owned());
+                println!("Seated as player {}, waiting for other players", player);
+            }
+            (Some("VIEW"), None) => println!(),
+            (Some("END"), None) => (),
+            (Some("TURN"), player) if player == seat.as_deref() => {
+                print!("MOVE> ");
+                io::stdout().flush().expect("Could not flush stdout");
+            }
+            (Some("TURN"), Some(player)) => println!("Waiting for player {}", player),
+            (Some("PLAYED"), Some(player)) => {
+                println!(
+                    "Player {} played {}",
+                    player,
+                    words.collect::<Vec<_>>().join(" ")
+                )
+            }
+            (Some("WELCOME"), _) => (),
+            (Some("FINISHED"), None) => println!("Game finished, result:"),
+            _ => println!("{}", line),
+        }
+    }
+    println!("Disconnected from server");
+    std::process::exit(0);
+}
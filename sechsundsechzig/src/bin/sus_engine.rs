@@ -0,0 +1,12 @@
+use std::io;
+
+use tbsux::engine::run_reference_engine;
+
+/// Reference engine playing random legal moves, for checking external engine support
+///
+/// Usage: `simulate --bots engine:path/to/sus_engine`
+fn main() {
+    if let Err(err) = run_reference_engine(io::stdin().lock(), io::stdout()) {
+        eprintln!("Engine stopped: {}", err);
+    }
+}
@@ -0,0 +1,30 @@
+use sechsundsechzig::api::{serve_http, Api};
+
+/// Serves JSON API, usage: `sus_http [ADDRESS] [--snapshot PATH]`
+///
+/// Built only with `http` feature, e.g. `cargo run --features http --bin sus_http`.
+fn main() {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    let address = args
+        .iter()
+        .find(|arg| arg.contains(':'))
+        .map_or("0.0.0.0:6680", String::as_str);
+    let snapshot = args
+        .iter()
+        .position(|arg| arg == "--snapshot")
+        .and_then(|index| args.get(index + 1));
+
+    let mut api = match snapshot {
+        Some(path) => match Api::with_snapshot(path) {
+            Ok(api) => api,
+            Err(err) => return eprintln!("Could not load snapshot {}: {}", path, err),
+        },
+        None => Api::new(),
+    };
+    let server = match tiny_http::Server::http(address) {
+        Ok(server) => server,
+        Err(err) => return eprintln!("Could not listen on {}: {}", address, err),
+    };
+    println!("Serving games on http://{}", address);
+    serve_http(&server, &mut api);
+}
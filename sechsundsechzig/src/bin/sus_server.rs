@@ -0,0 +1,68 @@
+use std::{net::TcpListener, time::Duration};
+
+use sechsundsechzig::{
+    game::SechsUndSechzig,
+    rules::Rules,
+    server::{serve, serve_timed},
+    variant::Variant,
+};
+use tbsux::clock::{TimeControl, TimedResult, TimeoutAction};
+
+/// Hosts a single game, usage: `sus_server [ADDRESS] [two|three] [--minutes MINUTES] [--rules RULES]`
+///
+/// With `--minutes`, every player gets given time for the game plus 5 seconds
+/// per move, and moves of players out of time are chosen randomly. Rules are
+/// read by `Rules::from_args`.
+fn main() {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    let address = args
+        .iter()
+        .find(|arg| arg.contains(':'))
+        .map_or("0.0.0.0:6666", String::as_str);
+    let variant = args
+        .iter()
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(Variant::FourPlayers);
+    let minutes = match args.iter().position(|arg| arg == "--minutes") {
+        Some(index) => match args
+            .get(index + 1)
+            .and_then(|minutes| minutes.parse::<u64>().ok())
+        {
+            Some(minutes) => Some(minutes),
+            None => return eprintln!("--minutes needs a whole number of minutes"),
+        },
+        None => None,
+    };
+    let rules = match Rules::from_args(&args, variant) {
+        Ok(rules) => rules,
+        Err(err) => return eprintln!("{}", err),
+    };
+
+    let listener = match TcpListener::bind(address) {
+        Ok(listener) => listener,
+        Err(err) => return eprintln!("Could not listen on {}: {}", address, err),
+    };
+    println!(
+        "Waiting for {} players on {}",
+        variant.number_of_players(),
+        address
+    );
+    let game = SechsUndSechzig::with_random_seed(variant).with_rules(rules);
+    let result = match minutes {
+        Some(minutes) => {
+            let control = TimeControl {
+                initial: Duration::from_secs(60 * minutes),
+                increment: Duration::from_secs(5),
+            };
+            // substitute moves are drawn from game's seed, so they can be replayed
+            let on_timeout = TimeoutAction::RandomMove(game.seed());
+            serve_timed(&listener, game, control, on_timeout)
+        }
+        None => serve(&listener, game).map(TimedResult::Finished),
+    };
+    match result {
+        Ok(TimedResult::Finished(score)) => print!("Game finished, result:\n{}", score),
+        Ok(TimedResult::Forfeited(player)) => println!("Player {} forfeited", player),
+        Err(err) => eprintln!("Game interrupted: {}", err),
+    }
+}
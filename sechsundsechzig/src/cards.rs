@@ -1,9 +1,11 @@
 use rand::prelude::*;
 use std::{fmt, str::FromStr};
 
-use crate::error::SechsUndSechzigError;
+use crate::error::{SechsUndSechzigError, SusResult};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Rank {
     Nine,
     Jack,
@@ -60,6 +62,8 @@ impl FromStr for Rank {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Suit {
     Spade,
     Club,
@@ -103,12 +107,22 @@ impl FromStr for Suit {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Card {
     pub rank: Rank,
     pub suit: Suit,
 }
 
 impl Card {
+    /// Cards written as comma-separated list, like `A ♥, 10 s`
+    pub fn parse_list(list: &str) -> SusResult<Vec<Card>> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|card| !card.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
     pub fn deck() -> impl Iterator<Item = Card> {
         Rank::RANKS.iter().flat_map(|rank| {
             Suit::SUITS.iter().map(move |suit| Card {
@@ -158,6 +172,12 @@ impl FromStr for Card {
     }
 }
 
+/// Cards of comma-separated list, for writing hands in tests
+#[cfg(test)]
+pub(crate) fn cards(list: &str) -> Vec<Card> {
+    Card::parse_list(list).unwrap()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -185,7 +205,14 @@ mod tests {
 
     #[test]
     fn whole_deck_is_worth_120_points() {
-        assert_eq!(120, Card::deck().map(|c| c.points()).sum())
+        assert_eq!(120, Card::deck().map(|c| c.points()).sum::<i32>())
+    }
+
+    #[test]
+    fn list_of_cards_is_parsed() {
+        assert_eq!(Ok(vec![ACE_OF_SPADES]), Card::parse_list(" a s, "));
+        assert_eq!(Ok(vec![]), Card::parse_list(""));
+        assert!(Card::parse_list("A s, 11 s").is_err());
     }
 
     #[test]
@@ -194,4 +221,12 @@ mod tests {
         assert_eq!(ACE_OF_SPADES, "A spade".parse().unwrap());
         assert_eq!(ACE_OF_SPADES, "aCe sPaDeS".parse().unwrap());
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn ace_of_spades_serializes_with_names() {
+        let json = serde_json::to_string(&ACE_OF_SPADES).unwrap();
+        assert_eq!(r#"{"rank":"ace","suit":"spade"}"#, json);
+        assert_eq!(ACE_OF_SPADES, serde_json::from_str(&json).unwrap());
+    }
 }
@@ -5,6 +5,8 @@ use tbsux::playered::Player;
 use crate::{cards::Suit, variant::Variant};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum GameType {
     NonTriumph,
     AskingAbout(Suit),
@@ -22,9 +24,36 @@ impl GameType {
             _ => None,
         }
     }
+
+    /// Name of game type, without its triumph
+    /// Names of game types, in order of variants
+    pub const NAMES: [&'static str; 5] = [
+        "non-triumph",
+        "asking-about",
+        "looking-for",
+        "misery",
+        "shower",
+    ];
+
+    pub fn name(&self) -> &'static str {
+        GameType::NAMES[self.index()]
+    }
+
+    /// Position of variant among game types, regardless of triumph
+    pub fn index(&self) -> usize {
+        use GameType::*;
+        match self {
+            NonTriumph => 0,
+            AskingAbout(_) => 1,
+            LookingFor(_) => 2,
+            Misery => 3,
+            Shower => 4,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Contract {
     pub game_type: GameType,
     pub dealer: Player,
@@ -43,7 +72,7 @@ impl Contract {
     pub fn dealers_teammate(&self, variant: &Variant) -> Option<Player> {
         use Variant::*;
         match variant {
-            ThreePlayers => None,
+            TwoPlayers | ThreePlayers => None,
             FourPlayers => Some((self.dealer + 2) % 4),
         }
     }
@@ -51,18 +80,27 @@ impl Contract {
     pub fn dealers_teammate_plays(&self) -> bool {
         use GameType::*;
 
-        match self.game_type {
-            Misery | Shower => false,
-            _ => true,
-        }
+        !matches!(self.game_type, Misery | Shower)
+    }
+
+    /// Players who take part in play stage
+    pub fn playing_players(&self, variant: &Variant) -> impl Iterator<Item = Player> {
+        let sitting_out = if self.dealers_teammate_plays() {
+            None
+        } else {
+            self.dealers_teammate(variant)
+        };
+        (0..variant.number_of_players()).filter(move |player| Some(*player) != sitting_out)
+    }
+
+    /// Points for winning the contract when it is worth `base_points` without raises
+    pub fn points(&self, base_points: i32) -> i32 {
+        base_points * self.multiplier
     }
 
     pub fn can_declare(&self) -> bool {
         use GameType::*;
-        match self.game_type {
-            AskingAbout(_) | LookingFor(_) => true,
-            _ => false,
-        }
+        matches!(self.game_type, AskingAbout(_) | LookingFor(_))
     }
 
     pub fn parties(&self, variant: &Variant) -> impl Iterator<Item = &Party> {
@@ -92,7 +130,10 @@ impl Contract {
             (SinglePlayer(player), _) => Box::new(once(*player)),
             (Dealers, None) => Box::new(once(dealer)),
             (Dealers, Some(mate)) => Box::new(twice(dealer, mate)),
-            (NonDealers, None) => Box::new(twice(next(dealer), next(next(dealer)))),
+            // in two-player game, player after the next one is dealer again
+            (NonDealers, None) => Box::new(
+                twice(next(dealer), next(next(dealer))).filter(move |player| *player != dealer),
+            ),
             (NonDealers, Some(mate)) => Box::new(twice(next(dealer), next(mate))),
         }
     }
@@ -111,13 +152,9 @@ impl Contract {
 
 impl fmt::Display for GameType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use GameType::*;
-        match self {
-            NonTriumph => write!(f, "non-triumph"),
-            AskingAbout(triumph) => write!(f, "asking-about, triumph: {}", triumph),
-            LookingFor(triumph) => write!(f, "looking-for, triumph: {}", triumph),
-            Misery => write!(f, "misery"),
-            Shower => write!(f, "shower"),
+        match self.triumph() {
+            Some(triumph) => write!(f, "{}, triumph: {}", self.name(), triumph),
+            None => write!(f, "{}", self.name()),
         }
     }
 }
@@ -132,7 +169,9 @@ impl fmt::Display for Contract {
     }
 }
 
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Party {
     Dealers,
     NonDealers,
@@ -145,7 +184,18 @@ impl Party {
         match self {
             Dealers => NonDealers,
             NonDealers => Dealers,
-            other => other.clone(),
+            other => *other,
+        }
+    }
+}
+
+impl fmt::Display for Party {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Party::*;
+        match self {
+            Dealers => write!(f, "dealers"),
+            NonDealers => write!(f, "non-dealers"),
+            SinglePlayer(player) => write!(f, "player {}", player),
         }
     }
 }
@@ -1,5 +1,7 @@
 use std::{error, fmt};
 
+use crate::cards::Card;
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum SechsUndSechzigError {
     InvaildTeam,
@@ -10,6 +12,8 @@ pub enum SechsUndSechzigError {
     SuitParseError,
     RankParseError,
     CardParseError,
+    RulesParseError,
+    VariantParseError,
 
     WrongStage,
 
@@ -17,6 +21,14 @@ pub enum SechsUndSechzigError {
 
     CardNotInHand,
     CardCannotBePlayed,
+    TalonMoveNotAllowed,
+
+    WrongNumberOfHands,
+    /// Hand of given player has too few or too many cards for variant
+    WrongHandSize(u32),
+    DuplicatedCard(Card),
+    /// Talon holds other number of cards than variant leaves after dealing
+    WrongTalonSize,
 }
 
 impl fmt::Display for SechsUndSechzigError {
@@ -1,17 +1,19 @@
 use core::fmt;
+use std::sync::Arc;
 
 use rand::prelude::*;
-use tbsux::{playered::Player, prelude::*};
+use tbsux::{
+    playered::{Player, View},
+    prelude::*,
+};
 
 use crate::{
     cards::Card,
-    contract::Contract,
     error::SechsUndSechzigError,
-    hands::Hands,
-    round::{Round, RoundResult},
+    round::{FinishedRound, PublicRound, Round, RoundResult},
+    rules::Rules,
     score::Score,
     sus_move::SusMove,
-    table::Table,
     team::Team,
     variant::Variant,
 };
@@ -19,15 +21,49 @@ use crate::{
 pub struct SechsUndSechzig {
     variant: Variant,
     seed: u64,
+    rules: Rules,
 }
 
 impl SechsUndSechzig {
-    pub fn with_random_seed(variant: Variant) -> SechsUndSechzig {
+    /// Game with deals drawn from `seed`, same seed and moves always lead to the same state
+    pub fn with_seed(variant: Variant, seed: u64) -> SechsUndSechzig {
         SechsUndSechzig {
             variant,
-            seed: thread_rng().next_u64(),
+            seed,
+            rules: Rules::for_variant(variant),
         }
     }
+
+    pub fn with_random_seed(variant: Variant) -> SechsUndSechzig {
+        SechsUndSechzig::with_seed(variant, thread_rng().next_u64())
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Same game played with given house rules
+    pub fn with_rules(self, rules: Rules) -> SechsUndSechzig {
+        SechsUndSechzig { rules, ..self }
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// Rebuilds state by applying `moves` one by one to initial state
+    pub fn replay(
+        &self,
+        moves: impl IntoIterator<Item = SusMove>,
+    ) -> Result<SechsUndSechzigState, SechsUndSechzigError> {
+        moves
+            .into_iter()
+            .try_fold(self.initial_state(), |state, mv| state.move_reducer(mv))
+    }
 }
 
 impl Game for SechsUndSechzig {
@@ -40,10 +76,13 @@ impl Game for SechsUndSechzig {
     fn initial_state(&self) -> Self::State {
         let mut rng = SeedableRng::seed_from_u64(self.seed);
         SechsUndSechzigState {
-            score: Score::empty(self.variant),
-            round: Round::first(&mut rng, &self.variant),
+            score: Score::with_target(self.variant, self.rules.target_score),
+            round: Round::first(&mut rng, &self.variant, self.rules),
             variant: self.variant,
             rng,
+            seed: self.seed,
+            history: History::default(),
+            results: Arc::default(),
         }
     }
 }
@@ -60,23 +99,108 @@ pub struct SechsUndSechzigState {
     rng: StdRng,
     round: Round,
     variant: Variant,
+    seed: u64,
+    history: History,
+    /// Shared between states, as it changes only once per round
+    results: Arc<Vec<FinishedRound>>,
+}
+
+/// Moves applied since initial state, newest first, sharing older moves between states
+#[derive(Debug, Clone, Default)]
+struct History(Option<Arc<(SusMove, History)>>);
+
+impl History {
+    fn with(&self, mv: SusMove) -> History {
+        History(Some(Arc::new((mv, self.clone()))))
+    }
+
+    fn to_vec(&self) -> Vec<SusMove> {
+        let mut moves = vec![];
+        let mut history = self;
+        while let Some(entry) = &history.0 {
+            moves.push(entry.0);
+            history = &entry.1;
+        }
+        moves.reverse();
+        moves
+    }
+}
+
+impl SechsUndSechzigState {
+    /// State in the middle of a game, with history starting at `round`
+    ///
+    /// Such state cannot be replayed from its seed, which only drives deals of following rounds.
+    pub(crate) fn from_round(score: Score, round: Round, seed: u64) -> SechsUndSechzigState {
+        SechsUndSechzigState {
+            score,
+            variant: round.variant(),
+            round,
+            rng: SeedableRng::seed_from_u64(seed),
+            seed,
+            history: History::default(),
+            results: Arc::default(),
+        }
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Seed of game this state comes from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Every move applied since initial state, oldest first
+    pub fn history(&self) -> Vec<SusMove> {
+        self.history.to_vec()
+    }
+
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    /// Results of every finished round, oldest first
+    pub fn results(&self) -> &[FinishedRound] {
+        &self.results
+    }
+
+    /// Round being played, or the next one when game has finished
+    pub fn round(&self) -> &Round {
+        &self.round
+    }
+
+    /// What `player` can see, also after the game has finished
+    pub fn player_view(&self, player: Player) -> SechsUndSechzigPlayerView {
+        self.view().player_view(player)
+    }
+
+    /// Game which, replayed with `history`, leads back to this state
+    pub fn game(&self) -> SechsUndSechzig {
+        SechsUndSechzig::with_seed(self.variant, self.seed).with_rules(*self.rules())
+    }
+
+    pub fn rules(&self) -> &Rules {
+        self.round.rules()
+    }
+
+    fn view(&self) -> SechsUndSechzigView {
+        SechsUndSechzigView {
+            score: self.score.clone(),
+            round: self.round.clone(),
+            results: self.results.clone(),
+        }
+    }
 }
 
 impl State<SechsUndSechzig> for SechsUndSechzigState {
     fn progress_report(&self) -> ProgressReport<SechsUndSechzig> {
         use ProgressReport::*;
 
-        if let Some(_) = self.score.winner() {
+        if self.score.winner().is_some() {
             Finished(self.score.clone())
         } else {
-            InProgress(SechsUndSechzigView {
-                score: self.score.clone(),
-                hands: self.round.hands().clone(),
-                current_player: self.round.current_player(),
-                contract: self.round.contract().clone(),
-                display_full_hand: self.round.display_full_hand(),
-                table: self.round.get_table(),
-            })
+            InProgress(self.view())
         }
     }
 
@@ -88,51 +212,69 @@ impl State<SechsUndSechzig> for SechsUndSechzigState {
         let move_result = cloned_round.handle_move(mv)?;
 
         Ok(SechsUndSechzigState {
-            round: match move_result {
+            round: match &move_result {
                 Contiune => cloned_round,
-                Finished(_, _, last_game_dealer) => {
-                    Round::new(&mut cloned_rng, &self.variant, last_game_dealer)
-                }
+                Finished(_) => Round::new(
+                    &mut cloned_rng,
+                    &self.variant,
+                    cloned_round.next_dealer(),
+                    *self.rules(),
+                ),
             },
             rng: cloned_rng,
             variant: self.variant,
+            seed: self.seed,
+            history: self.history.with(mv),
             score: {
                 let mut cloned_score = self.score.clone();
-                if let Finished(players, points, _) = move_result {
-                    let teams = Team::for_players(players, self.variant);
+                if let Finished(result) = &move_result {
+                    let teams = Team::for_players(result.winners.clone(), self.variant);
                     for team in teams {
-                        cloned_score.add_points(&team, points)?;
+                        cloned_score.add_points(&team, result.points())?;
                     }
                 }
                 cloned_score
             },
+            results: {
+                let mut results = self.results.clone();
+                if let Finished(result) = move_result {
+                    Arc::make_mut(&mut results).push(result);
+                }
+                results
+            },
         })
     }
+
+    fn legal_moves(&self) -> Vec<SusMove> {
+        if self.score.winner().is_some() {
+            vec![]
+        } else {
+            self.round.legal_moves()
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SechsUndSechzigView {
     score: Score,
-    current_player: Player,
-    hands: Hands,
-    contract: Contract,
-    table: Option<Table>,
-    display_full_hand: bool,
+    round: Round,
+    results: Arc<Vec<FinishedRound>>,
 }
 
 impl playered::View for SechsUndSechzigView {
     type PlayerView = SechsUndSechzigPlayerView;
 
     fn current_player(&self) -> playered::Player {
-        self.current_player
+        self.round.current_player()
     }
 
     fn player_view(&self, player: playered::Player) -> SechsUndSechzigPlayerView {
-        let hand: Vec<_> = if let Ok(hand) = self.hands.hand(&player) {
-            let mut h: Vec<Card> = if !self.display_full_hand {
-                hand.first().map(|c| c.clone()).collect()
+        let hand: Vec<_> = if let Ok(hand) = self.round.hands().hand(&player) {
+            let mut h: Vec<Card> = if !self.round.display_full_hand() {
+                hand.first().copied().collect()
             } else {
-                hand.full().map(|c| c.clone()).collect()
+                hand.full().copied().collect()
             };
             use std::cmp::Ordering::*;
             h.sort_by(|l, r| match l.suit.cmp(&r.suit) {
@@ -145,20 +287,49 @@ impl playered::View for SechsUndSechzigView {
         };
 
         SechsUndSechzigPlayerView {
+            player,
             score: self.score.clone(),
-            contract: self.contract.clone(),
-            table: self.table.clone(),
+            round: self.round.public(),
             hand,
+            results: self.results.clone(),
         }
     }
 }
 
+/// What a single player can see - own visible cards and public part of round
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SechsUndSechzigPlayerView {
+    player: Player,
     score: Score,
     hand: Vec<Card>,
-    contract: Contract,
-    table: Option<Table>,
+    round: PublicRound,
+    /// Shared with state and its other views
+    results: Arc<Vec<FinishedRound>>,
+}
+
+impl SechsUndSechzigPlayerView {
+    pub fn player(&self) -> Player {
+        self.player
+    }
+
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    /// Cards player can see in own hand - during first stage of bidding only first four of them
+    pub fn hand(&self) -> &[Card] {
+        &self.hand
+    }
+
+    pub fn round(&self) -> &PublicRound {
+        &self.round
+    }
+
+    /// Results of every finished round, oldest first
+    pub fn results(&self) -> &[FinishedRound] {
+        &self.results
+    }
 }
 
 impl fmt::Display for SechsUndSechzigView {
@@ -173,9 +344,20 @@ impl fmt::Display for SechsUndSechzigView {
 impl fmt::Display for SechsUndSechzigPlayerView {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "SCORE:\n{}", self.score)?;
-        writeln!(f, "CONTRACT:\n{}\n", self.contract)?;
+        if !self.results.is_empty() {
+            writeln!(f, "ROUNDS:")?;
+            for (index, result) in self.results.iter().enumerate() {
+                writeln!(f, "{}. {}", index + 1, result)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "CONTRACT:\n{}\n", self.round.contract())?;
 
-        if let Some(table) = &self.table {
+        if let Some(talon) = self.round.talon() {
+            writeln!(f, "TALON:\n{}\n", talon)?;
+        }
+
+        if let Some(table) = self.round.table() {
             writeln!(f, "TABLE:\n{}", table)?;
         }
 
@@ -188,3 +370,224 @@ impl fmt::Display for SechsUndSechzigPlayerView {
         write!(f, "HAND:\n{}", hand_view)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::{bidding::Bid, stash::Stashes, table::Table, talon::Talon};
+    use tbsux::{
+        agent::{run_agents, Agent, RandomAgent},
+        conformance::{check_conformance, ConformanceConfig},
+        playered::View,
+    };
+
+    fn game(variant: Variant) -> SechsUndSechzig {
+        SechsUndSechzig::with_seed(variant, 66)
+    }
+
+    #[test]
+    fn every_legal_move_is_accepted() {
+        for variant in [
+            Variant::TwoPlayers,
+            Variant::ThreePlayers,
+            Variant::FourPlayers,
+        ] {
+            let mut state = game(variant).initial_state();
+            for _ in 0..100 {
+                let legal_moves = state.legal_moves();
+                assert!(!legal_moves.is_empty());
+                for mv in &legal_moves {
+                    assert!(state.move_reducer(*mv).is_ok(), "{:?} rejected", mv);
+                }
+                state = state.move_reducer(legal_moves[0]).unwrap();
+            }
+        }
+    }
+
+    /// Every card of the deck is in exactly one of hands, table, stashes and talon
+    fn cards_are_conserved(state: &SechsUndSechzigState) -> Result<(), String> {
+        let round = &state.round;
+        let mut cards: Vec<Card> = vec![];
+        for player in 0..state.variant.number_of_players() {
+            let hand = round.hands().hand(&player).map_err(|err| err.to_string())?;
+            cards.extend(hand.full());
+        }
+        let public = round.public();
+        cards.extend(public.table().into_iter().flat_map(Table::cards));
+        cards.extend(public.stashes().into_iter().flat_map(Stashes::cards));
+        cards.extend(round.talon().map(Talon::cards).unwrap_or_default());
+
+        let points: i32 = cards.iter().map(Card::points).sum();
+        let unique: HashSet<_> = cards.iter().collect();
+        if cards.len() != 24 || unique.len() != 24 {
+            Err(format!(
+                "{} cards, {} of them unique",
+                cards.len(),
+                unique.len()
+            ))
+        } else if points != 120 {
+            Err(format!("cards are worth {} points", points))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn random_games_conform() {
+        for variant in [
+            Variant::TwoPlayers,
+            Variant::ThreePlayers,
+            Variant::FourPlayers,
+        ] {
+            let config = ConformanceConfig {
+                games: 300,
+                ..ConformanceConfig::default()
+            };
+            let result = check_conformance(
+                |seed| SechsUndSechzig::with_seed(variant, seed),
+                cards_are_conserved,
+                config,
+            );
+            assert_eq!(Ok(()), result, "{:?}", variant);
+        }
+    }
+
+    #[test]
+    fn initial_legal_moves_are_bids() {
+        let state = game(Variant::ThreePlayers).initial_state();
+        let legal_moves = state.legal_moves();
+        assert!(legal_moves.contains(&SusMove::BiddingMove(Bid::Pass)));
+        assert!(legal_moves
+            .iter()
+            .all(|mv| matches!(mv, SusMove::BiddingMove(_))));
+    }
+
+    #[test]
+    fn random_agents_finish_games() {
+        for variant in [
+            Variant::TwoPlayers,
+            Variant::ThreePlayers,
+            Variant::FourPlayers,
+        ] {
+            for seed in 0..20 {
+                let game = SechsUndSechzig::with_seed(variant, seed);
+                let mut agents: Vec<Box<dyn Agent<SechsUndSechzig>>> = (0..4)
+                    .map(|player| {
+                        Box::new(RandomAgent::with_seed(seed + player)) as Box<dyn Agent<_>>
+                    })
+                    .collect();
+                let score = run_agents(&game, &mut agents).unwrap();
+                assert!(score.winner().is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn finished_game_has_no_legal_moves() {
+        let mut state = game(Variant::ThreePlayers).initial_state();
+        state
+            .score
+            .add_points(&Team(0, Variant::ThreePlayers), 66)
+            .unwrap();
+        assert!(state.legal_moves().is_empty());
+    }
+
+    #[test]
+    fn replaying_history_rebuilds_state() {
+        for variant in [
+            Variant::TwoPlayers,
+            Variant::ThreePlayers,
+            Variant::FourPlayers,
+        ] {
+            let mut agent = RandomAgent::with_seed(0);
+            let mut state = game(variant).initial_state();
+            while let ProgressReport::InProgress(view) = state.progress_report() {
+                let mv = Agent::<SechsUndSechzig>::select_move(
+                    &mut agent,
+                    &view.player_view(view.current_player()),
+                    &state.legal_moves(),
+                )
+                .unwrap();
+                state = state.move_reducer(mv).unwrap();
+            }
+
+            let replayed = state.game().replay(state.history()).unwrap();
+            assert_eq!(state.history(), replayed.history());
+            assert_eq!(state.score, replayed.score);
+            for player in 0..variant.number_of_players() {
+                let hand = |state: &SechsUndSechzigState| -> Vec<Card> {
+                    state
+                        .round
+                        .hands()
+                        .hand(&player)
+                        .unwrap()
+                        .full()
+                        .copied()
+                        .collect()
+                };
+                assert_eq!(hand(&state), hand(&replayed));
+            }
+        }
+    }
+
+    #[test]
+    fn state_can_be_shared_between_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SechsUndSechzigState>();
+    }
+
+    #[test]
+    fn results_add_up_to_score() {
+        let variant = Variant::FourPlayers;
+        let mut state = game(variant).initial_state();
+        while let ProgressReport::InProgress(_) = state.progress_report() {
+            state = state.move_reducer(state.legal_moves()[0]).unwrap();
+        }
+        assert!(!state.results().is_empty());
+        for team in Team::teams(variant) {
+            let points: i32 = state
+                .results()
+                .iter()
+                .filter(|result| {
+                    Team::for_players(result.winners.clone(), variant).any(|t| t == team)
+                })
+                .map(FinishedRound::points)
+                .sum();
+            assert_eq!(state.score().team_points(&team), Some(points));
+        }
+        assert_eq!(state.results(), state.player_view(0).results());
+        // views share results with state instead of copying them
+        assert!(Arc::ptr_eq(&state.results, &state.player_view(0).results));
+    }
+
+    #[test]
+    fn replay_stops_at_illegal_move() {
+        let game = game(Variant::ThreePlayers);
+        let mv = game.initial_state().legal_moves()[0];
+        assert!(game.replay(vec![mv, mv, mv, mv]).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn views_survive_json_roundtrip() {
+        let mut state = game(Variant::FourPlayers).initial_state();
+        for _ in 0..10 {
+            state = state.move_reducer(state.legal_moves()[0]).unwrap();
+        }
+        let view = match state.progress_report() {
+            ProgressReport::InProgress(view) => view,
+            ProgressReport::Finished(_) => unreachable!(),
+        };
+        let player_view = view.player_view(view.current_player());
+
+        let json = serde_json::to_string(&player_view).unwrap();
+        let parsed: SechsUndSechzigPlayerView = serde_json::from_str(&json).unwrap();
+        assert_eq!(player_view.to_string(), parsed.to_string());
+
+        let json = serde_json::to_string(&view).unwrap();
+        let parsed: SechsUndSechzigView = serde_json::from_str(&json).unwrap();
+        assert_eq!(view.to_string(), parsed.to_string());
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use rand::prelude::*;
 use tbsux::playered::Player;
@@ -16,6 +16,7 @@ pub enum HandType {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hand(Vec<Card>);
 
 impl Hand {
@@ -31,13 +32,18 @@ impl Hand {
 
     pub fn deal(&mut self, card: Card) -> SusResult<Card> {
         if self.0.contains(&card) {
-            self.0 = self.full().map(|c| *c).filter(|c| c != &card).collect();
+            self.0 = self.full().copied().filter(|c| c != &card).collect();
             Ok(card)
         } else {
             Err(SechsUndSechzigError::CardNotInHand)
         }
     }
 
+    /// Adds card drawn from the talon
+    pub fn take(&mut self, card: Card) {
+        self.0.push(card);
+    }
+
     pub fn can_declare(&self, with_card: Card) -> bool {
         let is_queen = with_card.rank == Rank::Queen;
         let king_of_same_suit_in_hand = self.0.contains(&Card {
@@ -50,13 +56,23 @@ impl Hand {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hands(HashMap<Player, Hand>);
 
 impl Hands {
     pub fn deal(rng: &mut impl Rng, variant: &Variant) -> Hands {
+        Hands::deal_with_talon(rng, variant).0
+    }
+
+    /// Deals hands like `deal`, together with cards left for the talon
+    pub fn deal_with_talon(rng: &mut impl Rng, variant: &Variant) -> (Hands, Vec<Card>) {
         let shuffled_deck: Vec<_> = Card::shuffled_deck(rng).collect();
         let hands: HashMap<_, _> = (0..variant.number_of_players())
             .map(|player| {
@@ -67,7 +83,53 @@ impl Hands {
                 )
             })
             .collect();
-        Hands(hands)
+        let dealt = variant.number_of_players() as usize * variant.cards_per_player();
+        (Hands(hands), shuffled_deck[dealt..].into())
+    }
+
+    /// Hands holding given cards, `cards[player]` being hand of `player`, in given order
+    ///
+    /// Every card has to be dealt at most once. Unless variant leaves a talon,
+    /// whole deck is dealt, so a missing card shows up as a hand with too few cards.
+    pub fn from_deal(variant: &Variant, cards: Vec<Vec<Card>>) -> SusResult<Hands> {
+        let hands = Hands(
+            cards
+                .into_iter()
+                .enumerate()
+                .map(|(player, cards)| (player as Player, Hand(cards)))
+                .collect(),
+        );
+        hands.check_deal(variant)?;
+        Ok(hands)
+    }
+
+    /// Checks that hands hold different cards, split evenly between players of `variant`
+    pub fn check_deal(&self, variant: &Variant) -> SusResult<()> {
+        let players = variant.number_of_players();
+        if self.0.len() != players as usize {
+            return Err(SechsUndSechzigError::WrongNumberOfHands);
+        }
+        let mut dealt = HashSet::new();
+        for player in 0..players {
+            let hand = self.hand(&player)?;
+            if hand.len() != variant.cards_per_player() {
+                return Err(SechsUndSechzigError::WrongHandSize(player));
+            }
+            if let Some(card) = hand.full().find(|card| !dealt.insert(**card)) {
+                return Err(SechsUndSechzigError::DuplicatedCard(*card));
+            }
+        }
+        Ok(())
+    }
+
+    /// Hands holding given cards, in given order
+    pub(crate) fn from_cards(cards: HashMap<Player, Vec<Card>>) -> Hands {
+        Hands(
+            cards
+                .into_iter()
+                .map(|(player, cards)| (player, Hand(cards)))
+                .collect(),
+        )
     }
 
     pub fn hand(&self, player: &Player) -> SusResult<&Hand> {
@@ -86,10 +148,14 @@ impl Hands {
         }
     }
 
+    pub fn are_empty_for(&self, mut players: impl Iterator<Item = Player>) -> bool {
+        players.all(|player| self.0.get(&player).is_none_or(Hand::is_empty))
+    }
+
     pub fn are_empty(&self) -> bool {
         self.0
-            .iter()
-            .map(|(_, h)| h.is_empty())
+            .values()
+            .map(|h| h.is_empty())
             .reduce(|a, b| a && b)
             .unwrap_or(true)
     }
@@ -100,6 +166,7 @@ mod test {
     use std::collections::HashSet;
 
     use super::*;
+    use crate::cards::cards;
 
     #[test]
     fn deals_correctly_for_three_players() {
@@ -111,6 +178,19 @@ mod test {
         }
     }
 
+    #[test]
+    fn leaves_talon_for_two_players() {
+        let (dealt_hands, talon) = Hands::deal_with_talon(&mut thread_rng(), &Variant::TwoPlayers);
+        let mut cards: HashSet<_> = talon.iter().collect();
+        for player in 0..=1 {
+            let player_hand = dealt_hands.hand(&player).unwrap();
+            assert_eq!(6, player_hand.len());
+            cards.extend(player_hand.full());
+        }
+        assert_eq!(12, talon.len());
+        assert_eq!(24, cards.len());
+    }
+
     #[test]
     fn deals_correctly_for_four_players() {
         let dealt_hands = Hands::deal(&mut thread_rng(), &Variant::FourPlayers);
@@ -121,6 +201,47 @@ mod test {
         }
     }
 
+    fn four_players_deal() -> Vec<Vec<Card>> {
+        vec![
+            cards("9 s, J s, Q s, K s, 10 s, A s"),
+            cards("9 c, J c, Q c, K c, 10 c, A c"),
+            cards("9 d, J d, Q d, K d, 10 d, A d"),
+            cards("9 h, J h, Q h, K h, 10 h, A h"),
+        ]
+    }
+
+    #[test]
+    fn creates_hands_from_specified_deal() {
+        let hands = Hands::from_deal(&Variant::FourPlayers, four_players_deal()).unwrap();
+        let first: Vec<_> = hands.hand(&1).unwrap().first().copied().collect();
+        assert_eq!(cards("9 c, J c, Q c, K c"), first);
+    }
+
+    #[test]
+    fn rejects_invalid_deals() {
+        use SechsUndSechzigError::*;
+
+        let deal = four_players_deal();
+        assert_eq!(
+            Err(WrongNumberOfHands),
+            Hands::from_deal(&Variant::ThreePlayers, deal.clone()).map(|_| ())
+        );
+
+        let mut short = deal.clone();
+        short[2].pop();
+        assert_eq!(
+            Err(WrongHandSize(2)),
+            Hands::from_deal(&Variant::FourPlayers, short).map(|_| ())
+        );
+
+        let mut duplicated = deal;
+        duplicated[3][0] = duplicated[0][0];
+        assert_eq!(
+            Err(DuplicatedCard(duplicated[0][0])),
+            Hands::from_deal(&Variant::FourPlayers, duplicated.clone()).map(|_| ())
+        );
+    }
+
     #[test]
     fn deals_different_cards() {
         let dealt_hands = Hands::deal(&mut thread_rng(), &Variant::ThreePlayers);
@@ -1,3 +1,5 @@
+#[cfg(feature = "http")]
+pub mod api;
 pub mod bidding;
 pub mod cards;
 pub mod contract;
@@ -5,10 +7,21 @@ pub mod error;
 pub mod game;
 pub mod hands;
 pub mod ordering;
+pub mod record;
+pub mod revealed;
 pub mod round;
+pub mod rules;
+pub mod sampler;
+pub mod save;
 pub mod score;
+#[cfg(feature = "serde")]
+mod serde_pairs;
+pub mod server;
+pub mod solver;
 pub mod stash;
+pub mod stats;
 pub mod sus_move;
 pub mod table;
+pub mod talon;
 pub mod team;
 pub mod variant;
@@ -0,0 +1,694 @@
+//! Records of whole games in a portable text format, similar to PBN for bridge
+//!
+//! Record starts with a version line, followed by `[Tag "value"]` lines. Game
+//! tags name the variant and, optionally, the seed deals were drawn from and
+//! house rules, when they are not the default ones. Then every round lists its
+//! dealer, deal, bids, tricks, declarations, result and score of every team
+//! after it. Two-player rounds also list cards left in the talon, with face-up
+//! card last, and talon moves with number of trick they were made before.
+//! Records without a seed are replayed from deals they list. Cards and bids are
+//! written as moves, so any alias accepted by `Card`, `Suit` and `Rank` can be
+//! used:
+//!
+//! ```text
+//! % sechsundsechzig record 1
+//! [Variant "three"]
+//! [Seed "42"]
+//!
+//! [Round "1"]
+//! [Dealer "0"]
+//! [Deal "A ♥, 10 ♥, K ♥, Q ♥, J ♥, 9 ♥, A ♠, A ♣ / 9 ♠, J ♠, ... / K ♣, 10 ♣, ..."]
+//! [Bidding "misery, pass, pass"]
+//! [Contract "misery, multiplier: x1, dealer: player 0"]
+//! [Trick "0: A ♣, J ♣, 10 ♣"]
+//! [Result "1, 2: 7"]
+//! [Score "0, 7, 7"]
+//! ```
+//!
+//! Reading a record replays it, so declarations, results and scores it lists
+//! have to agree with its moves. Contract and unknown tags are only informative.
+
+use std::{error, fmt, str::FromStr};
+
+use tbsux::{playered::Player, prelude::*};
+
+use crate::{
+    bidding::Bid,
+    cards::{Card, Suit},
+    contract::Contract,
+    error::{SechsUndSechzigError, SusResult},
+    game::{SechsUndSechzig, SechsUndSechzigState},
+    hands::Hands,
+    round::{Round, RoundResult},
+    rules::Rules,
+    score::Score,
+    sus_move::SusMove,
+    talon::{Talon, TalonAction},
+    team::Team,
+    variant::Variant,
+};
+
+const HEADER: &str = "% sechsundsechzig record";
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RecordError {
+    UnsupportedVersion(String),
+    /// Line which could not be parsed, counting from 1, or 0 when record is empty
+    Malformed(usize),
+    MissingTag(&'static str),
+    /// Round, counting from 1, whose deal or moves could not be replayed
+    IllegalRound(usize, SechsUndSechzigError),
+    /// Round, counting from 1, which differs from its replay
+    Mismatch(usize),
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl error::Error for RecordError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRecord {
+    pub variant: Variant,
+    /// Seed deals are drawn from, `None` when they come from rounds themselves
+    pub seed: Option<u64>,
+    /// House rules, written as name of their preset or as their values
+    pub rules: Rules,
+    pub rounds: Vec<RoundRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundRecord {
+    /// Player who starts the bidding
+    pub dealer: Player,
+    /// Cards dealt to every player, in order of players
+    pub deal: Vec<Vec<Card>>,
+    /// Cards left in the talon, in order of drawing, empty unless variant has a talon
+    pub talon: Vec<Card>,
+    pub bids: Vec<Bid>,
+    /// Contract after the bidding, or the one being bid
+    pub contract: Contract,
+    /// Player who led every trick and cards played to it, last trick may be incomplete
+    pub tricks: Vec<(Player, Vec<Card>)>,
+    /// Talon moves, with number of trick counting from 1, which was led after them
+    pub talon_moves: Vec<(usize, TalonAction)>,
+    /// Player who declared and suit of declaration, for declarations which were counted
+    pub declarations: Vec<(Player, Suit)>,
+    /// Players who won the round and points they got, `None` for unfinished round
+    pub result: Option<(Vec<Player>, i32)>,
+    /// Points of every team after the round, in order of team ids
+    pub score: Vec<i32>,
+}
+
+impl GameRecord {
+    /// Record of game played so far, replayed from its seed and history
+    pub fn from_state(state: &SechsUndSechzigState) -> GameRecord {
+        let mut moves = state.history().into_iter().peekable();
+        let mut current = state.game().initial_state();
+        let mut rounds = vec![];
+        loop {
+            let (round, next) =
+                play_round(current, &mut moves).expect("History of a game contains legal moves");
+            let finished = round.result.is_some();
+            rounds.push(round);
+            current = next;
+            if moves.peek().is_none() && (!finished || current.score().winner().is_some()) {
+                break;
+            }
+        }
+        GameRecord {
+            variant: state.variant(),
+            seed: Some(state.seed()),
+            rules: *state.rules(),
+            rounds,
+        }
+    }
+
+    /// State reached after every move of the record
+    ///
+    /// Record without a seed has no state, as deals of following rounds are
+    /// unknown and its moves cannot be replayed from the start of a game.
+    pub fn state(&self) -> Result<SechsUndSechzigState, RecordError> {
+        if self.seed.is_none() {
+            return Err(RecordError::MissingTag("Seed"));
+        }
+        let rounds: Vec<_> = self.rounds.iter().map(WrittenRound::from).collect();
+        let (_, state) = replay(self.variant, self.seed, self.rules, &rounds)?;
+        Ok(state)
+    }
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}", HEADER, VERSION)?;
+        let variant = match self.variant {
+            Variant::TwoPlayers => "two",
+            Variant::ThreePlayers => "three",
+            Variant::FourPlayers => "four",
+        };
+        writeln!(f, "[Variant \"{}\"]", variant)?;
+        if let Some(seed) = self.seed {
+            writeln!(f, "[Seed \"{}\"]", seed)?;
+        }
+        if self.rules != Rules::for_variant(self.variant) {
+            writeln!(f, "[Rules \"{}\"]", self.rules)?;
+        }
+
+        for (index, round) in self.rounds.iter().enumerate() {
+            writeln!(f)?;
+            writeln!(f, "[Round \"{}\"]", index + 1)?;
+            writeln!(f, "[Dealer \"{}\"]", round.dealer)?;
+            let hands = round.deal.iter().map(|hand| cards_text(hand));
+            writeln!(f, "[Deal \"{}\"]", join(hands, " / "))?;
+            if !round.talon.is_empty() {
+                writeln!(f, "[Talon \"{}\"]", cards_text(&round.talon))?;
+            }
+            let bids = round.bids.iter().map(|bid| SusMove::BiddingMove(*bid));
+            writeln!(f, "[Bidding \"{}\"]", join(bids, ", "))?;
+            writeln!(f, "[Contract \"{}\"]", round.contract)?;
+            for (leader, cards) in &round.tricks {
+                writeln!(f, "[Trick \"{}: {}\"]", leader, cards_text(cards))?;
+            }
+            if !round.talon_moves.is_empty() {
+                let moves = round
+                    .talon_moves
+                    .iter()
+                    .map(|(trick, action)| format!("{} {}", trick, action));
+                writeln!(f, "[TalonMoves \"{}\"]", join(moves, ", "))?;
+            }
+            if !round.declarations.is_empty() {
+                let declarations = round
+                    .declarations
+                    .iter()
+                    .map(|(player, suit)| format!("{} {}", player, suit));
+                writeln!(f, "[Declarations \"{}\"]", join(declarations, ", "))?;
+            }
+            if let Some((winners, points)) = &round.result {
+                writeln!(f, "[Result \"{}: {}\"]", join(winners, ", "), points)?;
+            }
+            writeln!(f, "[Score \"{}\"]", join(&round.score, ", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for GameRecord {
+    type Err = RecordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()));
+
+        let (_, header) = lines.next().ok_or(RecordError::Malformed(0))?;
+        let version = header
+            .strip_prefix(HEADER)
+            .and_then(|version| version.trim().parse().ok());
+        if version != Some(VERSION) {
+            return Err(RecordError::UnsupportedVersion(header.to_owned()));
+        }
+
+        let mut written = WrittenGame::default();
+        for (number, line) in lines {
+            if line.is_empty() || line.starts_with('%') {
+                continue;
+            }
+            parse_tag(line)
+                .and_then(|(tag, value)| written.add_tag(tag, value))
+                .ok_or(RecordError::Malformed(number))?;
+        }
+
+        let variant = written.variant.ok_or(RecordError::MissingTag("Variant"))?;
+        if written.rounds.is_empty() {
+            return Err(RecordError::MissingTag("Round"));
+        }
+        let rules = written.rules.unwrap_or_else(|| Rules::for_variant(variant));
+        let (rounds, _) = replay(variant, written.seed, rules, &written.rounds)?;
+        Ok(GameRecord {
+            variant,
+            seed: written.seed,
+            rules,
+            rounds,
+        })
+    }
+}
+
+/// Game as written in a record, before it is replayed
+#[derive(Default)]
+struct WrittenGame {
+    variant: Option<Variant>,
+    seed: Option<u64>,
+    rules: Option<Rules>,
+    rounds: Vec<WrittenRound>,
+}
+
+impl WrittenGame {
+    /// Adds value of a tag, `None` when it cannot be parsed or appears in wrong place
+    fn add_tag(&mut self, tag: &str, value: &str) -> Option<()> {
+        match (tag, self.rounds.last_mut()) {
+            ("Variant", None) => self.variant = Some(value.parse().ok()?),
+            ("Seed", None) => self.seed = Some(value.parse().ok()?),
+            ("Rules", None) => self.rules = Some(value.parse().ok()?),
+            ("Round", _) => self.rounds.push(WrittenRound::default()),
+            ("Dealer", Some(round)) => round.dealer = Some(value.parse().ok()?),
+            ("Deal", Some(round)) => {
+                round.deal = Some(value.split('/').map(parse_cards).collect::<Option<_>>()?)
+            }
+            ("Talon", Some(round)) => round.talon = parse_cards(value)?,
+            ("Bidding", Some(round)) => {
+                round.bids = list(value)
+                    .map(|bid| match bid.parse() {
+                        Ok(SusMove::BiddingMove(bid)) => Some(bid),
+                        _ => None,
+                    })
+                    .collect::<Option<_>>()?
+            }
+            ("Trick", Some(round)) => {
+                let (leader, cards) = value.split_once(':')?;
+                round
+                    .tricks
+                    .push((leader.trim().parse().ok()?, parse_cards(cards)?));
+            }
+            ("TalonMoves", Some(round)) => {
+                round.talon_moves = list(value)
+                    .map(|talon_move| {
+                        let (trick, action) = talon_move.split_once(' ')?;
+                        match action.parse() {
+                            Ok(SusMove::TalonMove(action)) => Some((trick.parse().ok()?, action)),
+                            _ => None,
+                        }
+                    })
+                    .collect::<Option<_>>()?
+            }
+            ("Declarations", Some(round)) => {
+                round.declarations = list(value)
+                    .map(|declaration| {
+                        let (player, suit) = declaration.split_once(' ')?;
+                        Some((player.parse().ok()?, suit.trim().parse().ok()?))
+                    })
+                    .collect::<Option<_>>()?
+            }
+            ("Result", Some(round)) => {
+                let (winners, points) = value.split_once(':')?;
+                let mut winners: Vec<Player> = list(winners)
+                    .map(|player| player.parse().ok())
+                    .collect::<Option<_>>()?;
+                winners.sort_unstable();
+                round.result = Some((winners, points.trim().parse().ok()?));
+            }
+            ("Score", Some(round)) => {
+                round.score = Some(
+                    list(value)
+                        .map(|points| points.parse().ok())
+                        .collect::<Option<_>>()?,
+                )
+            }
+            ("Variant" | "Seed" | "Rules", Some(_)) => return None,
+            (
+                "Dealer" | "Deal" | "Talon" | "Bidding" | "Trick" | "TalonMoves" | "Declarations"
+                | "Result" | "Score",
+                None,
+            ) => return None,
+            _ => (),
+        }
+        Some(())
+    }
+}
+
+/// Round as written in a record, tags which were left out are `None`
+#[derive(Default)]
+struct WrittenRound {
+    dealer: Option<Player>,
+    deal: Option<Vec<Vec<Card>>>,
+    talon: Vec<Card>,
+    bids: Vec<Bid>,
+    tricks: Vec<(Player, Vec<Card>)>,
+    talon_moves: Vec<(usize, TalonAction)>,
+    declarations: Vec<(Player, Suit)>,
+    result: Option<(Vec<Player>, i32)>,
+    score: Option<Vec<i32>>,
+}
+
+impl WrittenRound {
+    fn moves(&self) -> impl Iterator<Item = SusMove> + '_ {
+        let bids = self.bids.iter().map(|bid| SusMove::BiddingMove(*bid));
+        // talon moves may also be made before a trick which was not led yet
+        let tricks = (0..=self.tricks.len()).flat_map(move |index| {
+            let talon_moves = self
+                .talon_moves
+                .iter()
+                .filter(move |(trick, _)| *trick == index + 1)
+                .map(|(_, action)| SusMove::TalonMove(*action));
+            let cards = self
+                .tricks
+                .get(index)
+                .into_iter()
+                .flat_map(|(_, cards)| cards.iter().map(|card| SusMove::PlayMove(*card)));
+            talon_moves.chain(cards)
+        });
+        bids.chain(tricks)
+    }
+
+    fn matches(&self, replayed: &RoundRecord) -> bool {
+        self.dealer.is_none_or(|dealer| dealer == replayed.dealer)
+            && self.deal.as_ref().is_none_or(|deal| deal == &replayed.deal)
+            && self.tricks == replayed.tricks
+            && self.talon_moves == replayed.talon_moves
+            && self.declarations == replayed.declarations
+            && self.result == replayed.result
+            && self
+                .score
+                .as_ref()
+                .is_none_or(|score| score == &replayed.score)
+    }
+}
+
+impl From<&RoundRecord> for WrittenRound {
+    fn from(round: &RoundRecord) -> WrittenRound {
+        WrittenRound {
+            dealer: Some(round.dealer),
+            deal: Some(round.deal.clone()),
+            talon: round.talon.clone(),
+            bids: round.bids.clone(),
+            tricks: round.tricks.clone(),
+            talon_moves: round.talon_moves.clone(),
+            declarations: round.declarations.clone(),
+            result: round.result.clone(),
+            score: Some(round.score.clone()),
+        }
+    }
+}
+
+/// Replays written rounds one after another, checking that they agree with their replay
+fn replay(
+    variant: Variant,
+    seed: Option<u64>,
+    rules: Rules,
+    rounds: &[WrittenRound],
+) -> Result<(Vec<RoundRecord>, SechsUndSechzigState), RecordError> {
+    let mut state = SechsUndSechzig::with_seed(variant, seed.unwrap_or(0))
+        .with_rules(rules)
+        .initial_state();
+    let mut replayed: Vec<RoundRecord> = vec![];
+    for (index, written) in rounds.iter().enumerate() {
+        let number = index + 1;
+        let previous_finished = replayed.last().is_none_or(|round| round.result.is_some());
+        if !previous_finished || state.score().winner().is_some() {
+            return Err(RecordError::Mismatch(number));
+        }
+
+        if seed.is_none() {
+            let dealer = written.dealer.ok_or(RecordError::MissingTag("Dealer"))?;
+            let deal = written
+                .deal
+                .clone()
+                .ok_or(RecordError::MissingTag("Deal"))?;
+            // following rounds are dealt by dealer of the last contract, or alternately by two players
+            if index > 0 && dealer != state.round().contract().dealer {
+                return Err(RecordError::Mismatch(number));
+            }
+            let round = Hands::from_deal(&variant, deal)
+                .and_then(|hands| {
+                    Round::from_deal(&variant, dealer, hands, written.talon.clone(), rules)
+                })
+                .map_err(|err| RecordError::IllegalRound(number, err))?;
+            state = SechsUndSechzigState::from_round(state.score().clone(), round, 0);
+        }
+
+        let mut moves = written.moves();
+        let (round, next) =
+            play_round(state, &mut moves).map_err(|err| RecordError::IllegalRound(number, err))?;
+        if moves.next().is_some() || !written.matches(&round) {
+            return Err(RecordError::Mismatch(number));
+        }
+        replayed.push(round);
+        state = next;
+    }
+    Ok((replayed, state))
+}
+
+/// Plays moves from the start of the current round, until they run out or the round finishes
+fn play_round(
+    mut state: SechsUndSechzigState,
+    moves: &mut impl Iterator<Item = SusMove>,
+) -> SusResult<(RoundRecord, SechsUndSechzigState)> {
+    let variant = state.variant();
+    // state deals next round right away, so finished round is followed on its own
+    let mut round = state.round().clone();
+    let dealer = round.contract().dealer;
+    let deal = (0..variant.number_of_players())
+        .map(|player| Ok(round.hands().hand(&player)?.full().copied().collect()))
+        .collect::<SusResult<_>>()?;
+    let talon = round.talon().map(Talon::cards).unwrap_or_default();
+    let mut bids = vec![];
+    let mut tricks: Vec<(Player, Vec<Card>)> = vec![];
+    let mut talon_moves = vec![];
+    let mut declarations = vec![];
+    let mut result = None;
+
+    for mv in moves {
+        let player = round.current_player();
+        let declared = declarations_count(&round);
+        let move_result = round.handle_move(mv)?;
+        state = state.move_reducer(mv)?;
+
+        match mv {
+            SusMove::BiddingMove(bid) => bids.push(bid),
+            SusMove::PlayMove(card) => {
+                let trick_size = round.contract().playing_players(&variant).count();
+                match tricks.last_mut() {
+                    Some((_, cards)) if cards.len() < trick_size => cards.push(card),
+                    _ => tricks.push((player, vec![card])),
+                }
+                if declarations_count(&round) > declared {
+                    // two players declare queen by leading it, not by ending the trick
+                    let declaration = match round.talon() {
+                        Some(_) => tricks
+                            .last()
+                            .map(|(leader, cards)| (*leader, cards[0].suit)),
+                        None => Some((player, card.suit)),
+                    };
+                    declarations.extend(declaration);
+                }
+            }
+            SusMove::TalonMove(action) => talon_moves.push((tricks.len() + 1, action)),
+        }
+        if let RoundResult::Finished(finished) = move_result {
+            let mut winners = finished.winners.clone();
+            winners.sort_unstable();
+            result = Some((winners, finished.points()));
+            break;
+        }
+    }
+
+    let record = RoundRecord {
+        dealer,
+        deal,
+        talon,
+        bids,
+        contract: round.contract().clone(),
+        tricks,
+        talon_moves,
+        declarations,
+        result,
+        score: team_points(state.score(), variant),
+    };
+    Ok((record, state))
+}
+
+fn declarations_count(round: &Round) -> usize {
+    round
+        .stashes()
+        .map_or(0, |stashes| stashes.declarations().count())
+}
+
+fn team_points(score: &Score, variant: Variant) -> Vec<i32> {
+    Team::teams(variant)
+        .map(|team| score.team_points(&team).unwrap_or(0))
+        .collect()
+}
+
+fn parse_tag(line: &str) -> Option<(&str, &str)> {
+    let (tag, value) = line.strip_prefix('[')?.strip_suffix(']')?.split_once(' ')?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((tag, value))
+}
+
+fn list(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+fn parse_cards(value: &str) -> Option<Vec<Card>> {
+    Card::parse_list(value).ok()
+}
+
+fn cards_text(cards: &[Card]) -> String {
+    join(
+        cards
+            .iter()
+            .map(|card| format!("{} {}", card.rank, card.suit)),
+        ", ",
+    )
+}
+
+fn join(items: impl IntoIterator<Item = impl fmt::Display>, separator: &str) -> String {
+    items
+        .into_iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finished_game(variant: Variant, seed: u64) -> SechsUndSechzigState {
+        play_out(SechsUndSechzig::with_seed(variant, seed))
+    }
+
+    fn play_out(game: SechsUndSechzig) -> SechsUndSechzigState {
+        let mut state = game.initial_state();
+        while let ProgressReport::InProgress(_) = state.progress_report() {
+            let moves = state.legal_moves();
+            state = state
+                .move_reducer(moves[state.history().len() % moves.len()])
+                .unwrap();
+        }
+        state
+    }
+
+    #[test]
+    fn written_record_is_read_back() {
+        for variant in [
+            Variant::TwoPlayers,
+            Variant::ThreePlayers,
+            Variant::FourPlayers,
+        ] {
+            let state = finished_game(variant, 5);
+            let record = GameRecord::from_state(&state);
+            let read: GameRecord = record.to_string().parse().unwrap();
+            assert_eq!(record, read);
+            assert!(read.rounds.iter().all(|round| round.result.is_some()));
+
+            let replayed = read.state().unwrap();
+            assert_eq!(state.history(), replayed.history());
+            assert_eq!(state.score(), replayed.score());
+        }
+    }
+
+    #[test]
+    fn rules_preset_is_recorded() {
+        let game =
+            SechsUndSechzig::with_seed(Variant::ThreePlayers, 3).with_rules(Rules::silesian());
+        let state = play_out(game);
+        let mut record = GameRecord::from_state(&state);
+        let text = record.to_string();
+        assert!(text.contains("[Rules \"silesian\"]"));
+        assert_eq!(record, text.parse().unwrap());
+        assert_eq!(state.score(), record.state().unwrap().score());
+
+        record.seed = None;
+        let read: GameRecord = record.to_string().parse().unwrap();
+        assert_eq!(Rules::silesian(), read.rules);
+        assert_eq!(record.rounds, read.rounds);
+    }
+
+    #[test]
+    fn custom_rules_are_recorded_by_values() {
+        let rules = Rules {
+            target_score: 30,
+            threshold: 60,
+            shower_allowed: false,
+            ..Rules::silesian()
+        };
+        let game = SechsUndSechzig::with_seed(Variant::FourPlayers, 2).with_rules(rules);
+        let state = play_out(game);
+        let record = GameRecord::from_state(&state);
+        let text = record.to_string();
+        assert!(text.contains("[Rules \"target 30, threshold 60, "));
+        let read: GameRecord = text.parse().unwrap();
+        assert_eq!(record, read);
+        assert_eq!(state.score(), read.state().unwrap().score());
+    }
+
+    #[test]
+    fn record_without_seed_is_replayed_from_deals() {
+        for variant in [Variant::TwoPlayers, Variant::FourPlayers] {
+            let state = finished_game(variant, 8);
+            let mut record = GameRecord::from_state(&state);
+            record.seed = None;
+            let read: GameRecord = record.to_string().parse().unwrap();
+            assert_eq!(record, read);
+            assert!(matches!(read.state(), Err(RecordError::MissingTag("Seed"))));
+        }
+    }
+
+    #[test]
+    fn talon_and_its_moves_are_recorded() {
+        let state = finished_game(Variant::TwoPlayers, 4);
+        let record = GameRecord::from_state(&state);
+        assert!(record.rounds.iter().all(|round| round.talon.len() == 12));
+        assert!(record
+            .rounds
+            .iter()
+            .any(|round| !round.talon_moves.is_empty()));
+        let text = record.to_string();
+        assert!(text.contains("[Variant \"two\"]"));
+        assert!(text.contains("[TalonMoves \""));
+    }
+
+    #[test]
+    fn cards_can_be_written_with_aliases() {
+        let record = GameRecord::from_state(&finished_game(Variant::ThreePlayers, 2));
+        let text = record
+            .to_string()
+            .replace('♥', "h")
+            .replace('♠', "spades")
+            .replace("10 ", "1 ")
+            .replace("Q ", "dama ");
+        assert_eq!(record, text.parse().unwrap());
+    }
+
+    #[test]
+    fn unfinished_game_ends_with_round_in_progress() {
+        let game = SechsUndSechzig::with_seed(Variant::ThreePlayers, 1);
+        let record = GameRecord::from_state(&game.initial_state());
+        assert_eq!(1, record.rounds.len());
+        assert_eq!(None, record.rounds[0].result);
+        assert_eq!(vec![0, 0, 0], record.rounds[0].score);
+        assert_eq!(record, record.to_string().parse().unwrap());
+    }
+
+    #[test]
+    fn rejects_score_which_does_not_follow_from_moves() {
+        let mut record = GameRecord::from_state(&finished_game(Variant::ThreePlayers, 3));
+        record.rounds[0].score[0] += 1;
+        assert_eq!(
+            Err(RecordError::Mismatch(1)),
+            record.to_string().parse::<GameRecord>()
+        );
+    }
+
+    #[test]
+    fn record_without_seed_needs_deals() {
+        let text =
+            "% sechsundsechzig record 1\n[Variant \"three\"]\n\n[Round \"1\"]\n[Dealer \"0\"]\n";
+        assert_eq!(
+            Err(RecordError::MissingTag("Deal")),
+            text.parse::<GameRecord>()
+        );
+        assert_eq!(
+            Err(RecordError::Malformed(2)),
+            "% sechsundsechzig record 1\n[Variant five]\n".parse::<GameRecord>()
+        );
+    }
+}
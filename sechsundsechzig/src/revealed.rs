@@ -0,0 +1,61 @@
+//! What players revealed about their hands during play of a round
+//!
+//! Player who did not follow suit has no cards of that suit, and player who
+//! declared a queen still holds king of the same suit, until it is played.
+
+use tbsux::playered::Player;
+
+use crate::cards::{Card, Rank, Suit};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Revealed {
+    voids: Vec<(Player, Suit)>,
+    held: Vec<(Player, Card)>,
+}
+
+impl Revealed {
+    /// Whether player is known to have no cards of `suit`
+    pub fn is_void(&self, player: Player, suit: Suit) -> bool {
+        self.voids.contains(&(player, suit))
+    }
+
+    /// Cards player is known to hold, including already played ones
+    pub fn held(&self, player: Player) -> impl Iterator<Item = Card> + '_ {
+        self.held
+            .iter()
+            .filter(move |(holder, _)| *holder == player)
+            .map(|(_, card)| *card)
+    }
+
+    /// Notes that `card` was played to a trick led with `led`, when suit had to be followed
+    pub fn followed(&mut self, player: Player, led: Card, card: Card, triumph: Option<Suit>) {
+        if card.suit == led.suit {
+            return;
+        }
+        self.void(player, led.suit);
+        // player who cannot follow suit has to play triumph
+        if let Some(triumph) =
+            triumph.filter(|triumph| *triumph != led.suit && *triumph != card.suit)
+        {
+            self.void(player, triumph);
+        }
+    }
+
+    /// Notes that player declared `suit`, so holds its king
+    pub fn declared(&mut self, player: Player, suit: Suit) {
+        self.held.push((
+            player,
+            Card {
+                rank: Rank::King,
+                suit,
+            },
+        ));
+    }
+
+    fn void(&mut self, player: Player, suit: Suit) {
+        if !self.is_void(player, suit) {
+            self.voids.push((player, suit));
+        }
+    }
+}
@@ -1,61 +1,384 @@
+use std::{collections::HashSet, fmt};
+
 use rand::prelude::*;
 use tbsux::playered::Player;
 
 use crate::{
-    bidding::{bidding, BidResult},
+    bidding::{bidding, Bid, BidResult},
+    cards::{Card, Suit},
     contract::{Contract, GameType, Party},
-    error::SechsUndSechzigError,
+    error::{SechsUndSechzigError, SusResult},
     hands::Hands,
+    revealed::Revealed,
+    rules::Rules,
     stash::Stashes,
     sus_move::SusMove,
     table::Table,
+    talon::{PublicTalon, Talon},
     variant::Variant,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum Stage {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub(crate) enum Stage {
     Bidding(Player),
     Play { table: Table, stashes: Stashes },
 }
 
 pub enum RoundResult {
     Contiune,
-    Finished(Vec<Player>, i32, Player),
+    Finished(FinishedRound),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RoundEnd {
+    /// Winner was known before every card was played
+    EarlyWin,
+    /// Playing players have no cards left
+    HandsEmpty,
+}
+
+/// Cards and declarations taken by a party during round
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartyPoints {
+    pub party: Party,
+    pub card_points: i32,
+    pub declarations: Vec<Suit>,
+    /// Points of cards and declarations together
+    pub points: i32,
+}
+
+/// How round ended and how many points its winners got
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FinishedRound {
+    pub contract: Contract,
+    /// Points of every party, in order of contract's parties
+    pub parties: Vec<PartyPoints>,
+    pub end: RoundEnd,
+    pub winning_party: Party,
+    pub winners: Vec<Player>,
+    /// Points for winning the contract, before multiplier of the contract
+    pub base_points: i32,
+}
+
+impl FinishedRound {
+    fn new(
+        variant: Variant,
+        rules: &Rules,
+        contract: &Contract,
+        stashes: &Stashes,
+        (winning_party, base_points): (Party, i32),
+        end: RoundEnd,
+    ) -> FinishedRound {
+        let triumph = contract.game_type.triumph();
+        let parties = contract
+            .parties(&variant)
+            .filter_map(|party| {
+                let stash = stashes.stash(party).ok()?;
+                Some(PartyPoints {
+                    party: *party,
+                    card_points: stash.card_points(),
+                    declarations: stash.declarations().copied().collect(),
+                    points: stash.points(triumph, rules),
+                })
+            })
+            .collect();
+        FinishedRound {
+            contract: contract.clone(),
+            parties,
+            end,
+            winning_party,
+            winners: contract
+                .players_in_party(&variant, &winning_party)
+                .collect(),
+            base_points,
+        }
+    }
+
+    /// Points scored by every winner
+    pub fn points(&self) -> i32 {
+        self.contract.points(self.base_points)
+    }
+}
+
+impl fmt::Display for FinishedRound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let end = match self.end {
+            RoundEnd::EarlyWin => "before all cards were played",
+            RoundEnd::HandsEmpty => "after all cards were played",
+        };
+        write!(
+            f,
+            "{}: {} won {} x{} = {} points {}",
+            self.contract,
+            self.winning_party,
+            self.base_points,
+            self.contract.multiplier,
+            self.points(),
+            end
+        )?;
+        for party in &self.parties {
+            write!(f, "; {} took {}", party.party, party.points)?;
+            if !party.declarations.is_empty() {
+                let declarations: Vec<_> = party
+                    .declarations
+                    .iter()
+                    .map(|suit| suit.to_string())
+                    .collect();
+                write!(
+                    f,
+                    " ({} in cards, declared {})",
+                    party.card_points,
+                    declarations.join(" ")
+                )?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Round {
     variant: Variant,
+    rules: Rules,
     initial_dealer: Player,
     hands: Hands,
     contract: Contract,
     stage: Stage,
+    /// Cards left to draw, only in variants which do not deal the whole deck
+    talon: Option<Talon>,
+    revealed: Revealed,
 }
 
-impl Round {
-    pub fn new(rng: &mut impl Rng, variant: &Variant, dealer: Player) -> Round {
+/// Part of round visible to every player - everything except hands
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PublicRound {
+    variant: Variant,
+    rules: Rules,
+    initial_dealer: Player,
+    contract: Contract,
+    stage: Stage,
+    hand_sizes: Vec<usize>,
+    talon: Option<PublicTalon>,
+    revealed: Revealed,
+}
+
+impl PublicRound {
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn contract(&self) -> &Contract {
+        &self.contract
+    }
+
+    pub fn table(&self) -> Option<&Table> {
+        match &self.stage {
+            Stage::Play { table, .. } => Some(table),
+            _ => None,
+        }
+    }
+
+    pub fn stashes(&self) -> Option<&Stashes> {
+        match &self.stage {
+            Stage::Play { stashes, .. } => Some(stashes),
+            _ => None,
+        }
+    }
+
+    pub fn hand_size(&self, player: Player) -> usize {
+        self.hand_sizes.get(player as usize).copied().unwrap_or(0)
+    }
+
+    /// Talon without its face-down cards
+    pub fn talon(&self) -> Option<&PublicTalon> {
+        self.talon.as_ref()
+    }
+
+    pub fn revealed(&self) -> &Revealed {
+        &self.revealed
+    }
+
+    /// Round in same stage, with given cards in players' hands and face down in talon
+    pub(crate) fn with_hands(&self, hands: Hands, stock: Vec<Card>) -> Round {
         Round {
+            variant: self.variant,
+            rules: self.rules,
+            initial_dealer: self.initial_dealer,
+            hands,
+            contract: self.contract.clone(),
+            stage: self.stage.clone(),
+            talon: self.talon.as_ref().map(|talon| talon.with_stock(stock)),
+            revealed: self.revealed.clone(),
+        }
+    }
+}
+
+impl Round {
+    pub fn new(rng: &mut impl Rng, variant: &Variant, dealer: Player, rules: Rules) -> Round {
+        let (hands, talon) = Hands::deal_with_talon(rng, variant);
+        Round::with_hands(variant, dealer, hands, talon, rules)
+    }
+
+    /// Round starting with bidding, with given cards dealt to players
+    ///
+    /// In two-player game there is no bidding, round starts with play and `talon`
+    /// holds cards left after dealing, in order of drawing, with face-up card last.
+    /// Other variants deal the whole deck, so their talon is empty.
+    pub fn from_deal(
+        variant: &Variant,
+        dealer: Player,
+        hands: Hands,
+        talon: Vec<Card>,
+        rules: Rules,
+    ) -> SusResult<Round> {
+        if dealer >= variant.number_of_players() {
+            return Err(SechsUndSechzigError::InvaildPlayer);
+        }
+        hands.check_deal(variant)?;
+        if talon.len() != variant.talon_size() {
+            return Err(SechsUndSechzigError::WrongTalonSize);
+        }
+        let mut dealt: HashSet<_> = (0..variant.number_of_players())
+            .flat_map(|player| hands.hand(&player).into_iter().flat_map(|hand| hand.full()))
+            .collect();
+        if let Some(card) = talon.iter().find(|card| !dealt.insert(card)) {
+            return Err(SechsUndSechzigError::DuplicatedCard(*card));
+        }
+        Ok(Round::with_hands(variant, dealer, hands, talon, rules))
+    }
+
+    fn with_hands(
+        variant: &Variant,
+        dealer: Player,
+        hands: Hands,
+        talon: Vec<Card>,
+        rules: Rules,
+    ) -> Round {
+        let mut round = Round {
             variant: *variant,
+            rules,
             initial_dealer: dealer,
-            hands: Hands::deal(rng, variant),
+            hands,
             contract: Contract::initial(dealer),
             stage: Stage::Bidding(dealer),
+            talon: Talon::new(talon),
+            revealed: Revealed::default(),
+        };
+        // two players play asking-about in suit of face-up card, which is led by non-dealer
+        if let Some(talon) = &round.talon {
+            round.contract.game_type = GameType::AskingAbout(talon.triumph());
+            round.stage = round.play_stage(variant.next_player(dealer));
         }
+        round
     }
 
-    pub fn first(rng: &mut impl Rng, variant: &Variant) -> Round {
+    fn play_stage(&self, leader: Player) -> Stage {
+        Stage::Play {
+            table: Table::empty(self.variant, self.contract.clone(), leader),
+            stashes: Stashes::empty(self.contract.parties(&self.variant)),
+        }
+    }
+
+    /// Round in play stage, with given cards left in hands, on table and taken by parties
+    ///
+    /// Two-player round needs its `talon`, for example exhausted or closed one,
+    /// other variants have none.
+    pub fn in_play(
+        variant: Variant,
+        hands: Hands,
+        contract: Contract,
+        table: Table,
+        stashes: Stashes,
+        talon: Option<Talon>,
+        rules: Rules,
+    ) -> Round {
+        debug_assert_eq!(variant.has_talon(), talon.is_some());
+        Round {
+            variant,
+            rules,
+            initial_dealer: contract.dealer,
+            hands,
+            contract,
+            stage: Stage::Play { table, stashes },
+            talon,
+            revealed: Revealed::default(),
+        }
+    }
+
+    pub fn first(rng: &mut impl Rng, variant: &Variant, rules: Rules) -> Round {
         let random_dealer = rng.gen_range(0..variant.number_of_players());
-        Round::new(rng, variant, random_dealer)
+        Round::new(rng, variant, random_dealer, rules)
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
     }
 
     pub fn hands(&self) -> &Hands {
         &self.hands
     }
 
+    pub fn talon(&self) -> Option<&Talon> {
+        self.talon.as_ref()
+    }
+
+    /// Player dealing the next round, dealers alternate in two-player game
+    pub fn next_dealer(&self) -> Player {
+        match self.talon {
+            Some(_) => self.variant.next_player(self.initial_dealer),
+            None => self.contract.dealer,
+        }
+    }
+
     pub fn contract(&self) -> &Contract {
         &self.contract
     }
 
+    pub fn table(&self) -> Option<&Table> {
+        match &self.stage {
+            Stage::Play { table, .. } => Some(table),
+            _ => None,
+        }
+    }
+
+    pub fn stashes(&self) -> Option<&Stashes> {
+        match &self.stage {
+            Stage::Play { stashes, .. } => Some(stashes),
+            _ => None,
+        }
+    }
+
+    pub fn public(&self) -> PublicRound {
+        PublicRound {
+            variant: self.variant,
+            rules: self.rules,
+            initial_dealer: self.initial_dealer,
+            contract: self.contract.clone(),
+            stage: self.stage.clone(),
+            hand_sizes: (0..self.variant.number_of_players())
+                .map(|player| self.hands.hand(&player).map_or(0, |hand| hand.len()))
+                .collect(),
+            talon: self.talon.as_ref().map(Talon::public),
+            revealed: self.revealed.clone(),
+        }
+    }
+
     pub fn current_player(&self) -> Player {
         use Stage::*;
 
@@ -65,6 +388,51 @@ impl Round {
         }
     }
 
+    pub fn legal_moves(&self) -> Vec<SusMove> {
+        use Stage::*;
+        use SusMove::*;
+
+        match &self.stage {
+            Bidding(player) => Bid::all()
+                .filter(|bid| {
+                    bidding(
+                        &self.contract,
+                        bid,
+                        *player,
+                        &self.variant,
+                        self.initial_dealer,
+                        &self.rules,
+                    )
+                    .is_ok()
+                })
+                .map(BiddingMove)
+                .collect(),
+            Play { table, .. } => {
+                let hand = match table
+                    .current_player()
+                    .and_then(|player| self.hands.hand(&player).ok())
+                {
+                    Some(hand) => hand,
+                    None => return vec![],
+                };
+                match &self.talon {
+                    // while cards are drawn, any card can be played
+                    Some(talon) if talon.is_open() => {
+                        let talon_moves = talon
+                            .actions(hand)
+                            .filter(|_| table.deals().is_empty())
+                            .map(TalonMove);
+                        hand.full()
+                            .map(|card| PlayMove(*card))
+                            .chain(talon_moves)
+                            .collect()
+                    }
+                    _ => table.filter_hand(hand).map(PlayMove).collect(),
+                }
+            }
+        }
+    }
+
     pub fn handle_move(&mut self, mv: SusMove) -> Result<RoundResult, SechsUndSechzigError> {
         use RoundResult::*;
         use Stage::*;
@@ -79,6 +447,7 @@ impl Round {
                     *player,
                     &self.variant,
                     self.initial_dealer,
+                    &self.rules,
                 )? {
                     BidResult::Continue(new_contract, new_player) => {
                         self.contract = new_contract;
@@ -87,56 +456,127 @@ impl Round {
                     }
                     BidResult::Finish(final_contract) => {
                         self.contract = final_contract;
-                        self.stage = Play {
-                            table: Table::empty(
-                                self.variant,
-                                self.contract.clone(),
-                                self.contract.dealer,
-                            ),
-                            stashes: Stashes::empty(self.contract.parties(&self.variant)),
-                        };
+                        self.stage = self.play_stage(self.contract.dealer);
                         Ok(Contiune)
                     }
                 }
             }
             (Play { table, stashes }, PlayMove(card)) => {
                 let hand = self.hands.hand_mut(&current_player).expect("Correct hand");
-                let is_declaration = hand.can_declare(card) && self.contract.can_declare();
-                table.try_play_card(hand, card)?;
+                let can_declare = self.contract.can_declare();
+                let is_declaration = hand.can_declare(card) && can_declare;
+                match &self.talon {
+                    Some(talon) if talon.is_open() => {
+                        hand.deal(card)?;
+                        table.play_card(card)?;
+                    }
+                    _ => {
+                        table.try_play_card(hand, card)?;
+                        if let Some((_, led)) = table.deals().first() {
+                            let triumph = self.contract.game_type.triumph();
+                            self.revealed.followed(current_player, *led, card, triumph);
+                        }
+                    }
+                }
 
                 if let Some(drawer) = table.drawer() {
-                    let drawing_party = self.contract.players_party(self.variant, drawer);
-                    {
-                        let drawing_party_stash = stashes.stash_mut(&drawing_party)?;
-                        if is_declaration {
-                            drawing_party_stash.declare(card.suit);
-                        }
-                        drawing_party_stash.add_cards(table.cards());
+                    let declaration = match (&self.talon, table.deals().first()) {
+                        // with two players, queen is declared by leading it
+                        (Some(_), Some((leader, led))) => self
+                            .hands
+                            .hand(leader)
+                            .ok()
+                            .filter(|hand| can_declare && hand.can_declare(*led))
+                            .map(|_| (*leader, led.suit)),
+                        _ => Some((drawer, card.suit)).filter(|_| is_declaration),
+                    };
+                    if let Some((declarer, suit)) = declaration {
+                        let declaring_party = self.contract.players_party(self.variant, declarer);
+                        stashes.stash_mut(&declaring_party)?.declare(suit);
+                        // declaration counts for drawer, but king is held by player of queen
+                        let holder = match &self.talon {
+                            Some(_) => declarer,
+                            None => current_player,
+                        };
+                        self.revealed.declared(holder, suit);
                     }
+                    let drawing_party = self.contract.players_party(self.variant, drawer);
+                    stashes.stash_mut(&drawing_party)?.add_cards(table.cards());
                     *table = Table::empty(self.variant, self.contract.clone(), drawer);
 
-                    if let Some((winning_party, points)) =
-                        Round::immediate_winner(&stashes, drawer, &self.contract)
-                    {
-                        let winners: Vec<_> = self
-                            .contract
-                            .players_in_party(&self.variant, &winning_party)
-                            .collect();
-                        return Ok(Finished(winners, points.clone(), self.contract.dealer));
+                    if let Some(winner) = Round::immediate_winner(
+                        stashes,
+                        drawer,
+                        &self.contract,
+                        self.variant,
+                        &self.rules,
+                    ) {
+                        let winner = Round::after_closing(
+                            self.talon.as_ref(),
+                            &self.contract,
+                            self.variant,
+                            &self.rules,
+                            winner,
+                            RoundEnd::EarlyWin,
+                        );
+                        return Ok(Finished(FinishedRound::new(
+                            self.variant,
+                            &self.rules,
+                            &self.contract,
+                            stashes,
+                            winner,
+                            RoundEnd::EarlyWin,
+                        )));
                     }
 
-                    if self.hands.are_empty() {
-                        let (winning_party, points) =
-                            Round::winner(&stashes, drawer, &self.contract, self.variant);
-                        let winners: Vec<_> = self
-                            .contract
-                            .players_in_party(&self.variant, &winning_party)
-                            .collect();
-                        return Ok(Finished(winners, points.clone(), self.contract.dealer));
+                    // trick's winner draws first
+                    if let Some(talon) = self.talon.as_mut().filter(|talon| talon.is_open()) {
+                        for player in [drawer, self.variant.next_player(drawer)] {
+                            if let Some(card) = talon.draw() {
+                                self.hands.hand_mut(&player)?.take(card);
+                            }
+                        }
+                    }
+
+                    if self
+                        .hands
+                        .are_empty_for(self.contract.playing_players(&self.variant))
+                    {
+                        let winner = Round::winner(
+                            stashes,
+                            drawer,
+                            &self.contract,
+                            self.variant,
+                            &self.rules,
+                        );
+                        let winner = Round::after_closing(
+                            self.talon.as_ref(),
+                            &self.contract,
+                            self.variant,
+                            &self.rules,
+                            winner,
+                            RoundEnd::HandsEmpty,
+                        );
+                        return Ok(Finished(FinishedRound::new(
+                            self.variant,
+                            &self.rules,
+                            &self.contract,
+                            stashes,
+                            winner,
+                            RoundEnd::HandsEmpty,
+                        )));
                     }
                 }
                 Ok(Contiune)
             }
+            (Play { table, .. }, TalonMove(action)) => match &mut self.talon {
+                Some(talon) if table.deals().is_empty() => {
+                    let hand = self.hands.hand_mut(&current_player)?;
+                    talon.take(action, current_player, hand)?;
+                    Ok(Contiune)
+                }
+                _ => Err(SechsUndSechzigError::TalonMoveNotAllowed),
+            },
             _ => Err(SechsUndSechzigError::WrongStage),
         }
     }
@@ -156,52 +596,74 @@ impl Round {
         }
     }
 
+    /// Player who closed the talon has to reach the threshold, otherwise the opponent
+    /// wins at least as much as for asking-about with opponents below half of it
+    fn after_closing(
+        talon: Option<&Talon>,
+        contract: &Contract,
+        variant: Variant,
+        rules: &Rules,
+        (party, points): (Party, i32),
+        end: RoundEnd,
+    ) -> (Party, i32) {
+        let closing_party = match talon.and_then(Talon::closed_by) {
+            Some(closer) => contract.players_party(variant, closer),
+            None => return (party, points),
+        };
+        if party == closing_party && end == RoundEnd::EarlyWin {
+            (party, points)
+        } else {
+            (
+                closing_party.other(),
+                points.max(rules.points.asking_about[1]),
+            )
+        }
+    }
+
     fn immediate_winner(
         stashes: &Stashes,
         last_drawer: Player,
         contract: &Contract,
+        variant: Variant,
+        rules: &Rules,
     ) -> Option<(Party, i32)> {
         use GameType::*;
         use Party::*;
 
-        let points = stashes.points(contract.game_type.triumph());
+        let points = stashes.points(contract.game_type.triumph(), rules);
+        let table = &rules.points;
 
         match contract.game_type {
             NonTriumph => None,
-            AskingAbout(_) => points
-                .iter()
-                .filter(|(_, points)| **points >= 66)
-                .map(|(party, _)| *party)
-                .next()
-                .map(|party| {
-                    (
-                        party,
-                        match points[&party.other()] {
-                            0 => 3,
-                            1..=32 => 2,
-                            _ => 1,
-                        },
-                    )
-                }),
+            AskingAbout(_) => {
+                // declaration of trick's leader can get both parties over threshold at once,
+                // then party of drawer wins
+                let drawers = contract.players_party(variant, last_drawer);
+                [drawers, drawers.other()]
+                    .iter()
+                    .copied()
+                    .find(|party| points[party] >= rules.threshold)
+                    .map(|party| (party, rules.asking_about_points(points[&party.other()])))
+            }
             LookingFor(_) => {
                 if last_drawer != contract.dealer {
-                    Some((NonDealers, 5))
-                } else if points[&Dealers] > 66 {
-                    Some((Dealers, 5))
+                    Some((NonDealers, table.looking_for))
+                } else if points[&Dealers] > rules.threshold {
+                    Some((Dealers, table.looking_for))
                 } else {
                     None
                 }
             }
             Misery => {
                 if points[&Dealers] > 0 {
-                    Some((NonDealers, 7))
+                    Some((NonDealers, table.misery))
                 } else {
                     None
                 }
             }
             Shower => {
                 if points[&NonDealers] > 0 {
-                    Some((NonDealers, 10))
+                    Some((NonDealers, table.shower))
                 } else {
                     None
                 }
@@ -214,24 +676,388 @@ impl Round {
         last_drawer: Player,
         contract: &Contract,
         variant: Variant,
+        rules: &Rules,
     ) -> (Party, i32) {
         use GameType::*;
         use Party::*;
 
+        let table = &rules.points;
         match contract.game_type {
             NonTriumph => (
                 stashes
-                    .points(contract.game_type.triumph())
+                    .points(contract.game_type.triumph(), rules)
                     .iter()
-                    .min_by_key(|(_, points)| *points)
+                    // ties are broken by order of parties, so replayed games end the same way
+                    .min_by_key(|(party, points)| (**points, **party))
                     .map(|(party, _)| *party)
                     .expect("This is not empty"),
-                1,
+                table.non_triumph,
+            ),
+            AskingAbout(_) => (
+                contract.players_party(variant, last_drawer),
+                table.asking_about_last_trick,
             ),
-            AskingAbout(_) => (contract.players_party(variant, last_drawer), 1),
-            LookingFor(_) => (Dealers, 5),
-            Misery => (Dealers, 7),
-            Shower => (Dealers, 10),
+            LookingFor(_) => (Dealers, table.looking_for),
+            Misery => (Dealers, table.misery),
+            Shower => (Dealers, table.shower),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::cards;
+    use crate::talon::TalonAction;
+    use Bid::*;
+    use SusMove::*;
+
+    /// Player 0 holds every heart and both remaining aces, so takes every trick
+    fn round() -> Round {
+        let hands = Hands::from_deal(
+            &Variant::ThreePlayers,
+            vec![
+                cards("A h, 10 h, K h, Q h, J h, 9 h, A s, A c"),
+                cards("9 s, J s, Q s, K s, 10 s, 9 c, J c, Q c"),
+                cards("K c, 10 c, 9 d, J d, Q d, K d, 10 d, A d"),
+            ],
+        )
+        .unwrap();
+        Round::from_deal(&Variant::ThreePlayers, 0, hands, vec![], Rules::default()).unwrap()
+    }
+
+    /// Two-player round with hearts as triumph, player 0 holds their nine
+    fn two_players_round(dealer: Player) -> Round {
+        let hands = Hands::from_deal(
+            &Variant::TwoPlayers,
+            vec![
+                cards("9 s, J s, Q s, K s, 9 h, J c"),
+                cards("A c, 10 c, Q h, K h, A s, 10 s"),
+            ],
+        )
+        .unwrap();
+        let talon = cards("9 c, Q c, K c, 9 d, J d, Q d, K d, 10 d, A d, J h, 10 h, A h");
+        Round::from_deal(&Variant::TwoPlayers, dealer, hands, talon, Rules::default()).unwrap()
+    }
+
+    fn held(round: &Round, player: Player) -> Vec<Card> {
+        round
+            .hands()
+            .hand(&player)
+            .unwrap()
+            .full()
+            .copied()
+            .collect()
+    }
+
+    /// Plays given bids, then every player plays first card they can until round ends
+    fn finish(round: &mut Round, bids: &[Bid]) -> FinishedRound {
+        for bid in bids {
+            round.handle_move(BiddingMove(*bid)).unwrap();
+        }
+        loop {
+            let mv = round.legal_moves()[0];
+            if let RoundResult::Finished(result) = round.handle_move(mv).unwrap() {
+                return result;
+            }
+        }
+    }
+
+    fn play(round: &mut Round, bids: &[Bid]) -> (Vec<Player>, i32) {
+        let result = finish(round, bids);
+        (result.winners.clone(), result.points())
+    }
+
+    #[test]
+    fn dealer_taking_every_trick_wins_looking_for() {
+        let mut round = round();
+        let bids = [
+            Game(GameType::AskingAbout(Suit::Heart)),
+            Game(GameType::LookingFor(Suit::Heart)),
+            Pass,
+            Pass,
+        ];
+        assert_eq!((vec![0], 5), play(&mut round, &bids));
+    }
+
+    #[test]
+    fn dealer_taking_every_trick_wins_shower() {
+        let mut round = round();
+        let bids = [
+            Game(GameType::AskingAbout(Suit::Heart)),
+            Game(GameType::Shower),
+            Pass,
+            Pass,
+        ];
+        assert_eq!((vec![0], 10), play(&mut round, &bids));
+    }
+
+    #[test]
+    fn dealer_taking_points_loses_misery() {
+        let mut round = round();
+        let bids = [Game(GameType::Misery), Pass, Pass];
+        assert_eq!((vec![1, 2], 7), play(&mut round, &bids));
+    }
+
+    #[test]
+    fn result_explains_points() {
+        let mut misery = round();
+        let result = finish(&mut misery, &[Game(GameType::Misery), Pass, Pass]);
+        assert_eq!(RoundEnd::EarlyWin, result.end);
+        assert_eq!(Party::NonDealers, result.winning_party);
+        assert_eq!((7, 1), (result.base_points, result.contract.multiplier));
+        let dealers = &result.parties[0];
+        assert_eq!(Party::Dealers, dealers.party);
+        assert!(dealers.card_points > 0);
+
+        let mut shower = round();
+        let bids = [
+            Game(GameType::AskingAbout(Suit::Heart)),
+            Game(GameType::Shower),
+            Pass,
+            Pass,
+        ];
+        let result = finish(&mut shower, &bids);
+        assert_eq!(RoundEnd::HandsEmpty, result.end);
+        let points: Vec<_> = result
+            .parties
+            .iter()
+            .map(|party| (party.party, party.card_points, party.points))
+            .collect();
+        assert_eq!(
+            vec![(Party::Dealers, 120, 120), (Party::NonDealers, 0, 0)],
+            points
+        );
+    }
+
+    #[test]
+    fn points_are_multiplied_for_every_game_type() {
+        let game_types = [
+            GameType::NonTriumph,
+            GameType::AskingAbout(Suit::Heart),
+            GameType::LookingFor(Suit::Heart),
+            GameType::Misery,
+            GameType::Shower,
+        ];
+        let variant = Variant::ThreePlayers;
+        for game_type in game_types {
+            let results: Vec<_> = [1, 2, 4, 16]
+                .iter()
+                .map(|multiplier| {
+                    let contract = Contract {
+                        game_type,
+                        dealer: 0,
+                        multiplier: *multiplier,
+                    };
+                    let mut round = Round::in_play(
+                        variant,
+                        round().hands().clone(),
+                        contract.clone(),
+                        Table::empty(variant, contract.clone(), 0),
+                        Stashes::empty(contract.parties(&variant)),
+                        None,
+                        Rules::default(),
+                    );
+                    finish(&mut round, &[])
+                })
+                .collect();
+            let base_points = results[0].points();
+            for result in results {
+                assert_eq!(base_points, result.base_points, "{}", game_type);
+                assert_eq!(
+                    base_points * result.contract.multiplier,
+                    result.points(),
+                    "{}",
+                    game_type
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn raise_doubles_points_of_round() {
+        let mut round = round();
+        let result = finish(&mut round, &[Pass, Pass, Raise]);
+        assert_eq!(2, result.contract.multiplier);
+        assert_eq!(2 * result.base_points, result.points());
+    }
+
+    #[test]
+    fn points_follow_house_rules() {
+        let base_points = |rules: Rules| {
+            let hands = round().hands().clone();
+            let mut round =
+                Round::from_deal(&Variant::ThreePlayers, 0, hands, vec![], rules).unwrap();
+            finish(&mut round, &[Game(GameType::Misery), Pass, Pass]).base_points
+        };
+        assert_eq!(7, base_points(Rules::warsaw()));
+        assert_eq!(8, base_points(Rules::silesian()));
+    }
+
+    #[test]
+    fn rejects_dealer_outside_of_table() {
+        let hands = round().hands().clone();
+        assert!(matches!(
+            Round::from_deal(&Variant::ThreePlayers, 3, hands, vec![], Rules::default()),
+            Err(SechsUndSechzigError::InvaildPlayer)
+        ));
+    }
+
+    #[test]
+    fn two_players_start_with_play_led_by_non_dealer() {
+        let round = two_players_round(0);
+        assert_eq!(
+            GameType::AskingAbout(Suit::Heart),
+            round.contract().game_type
+        );
+        assert_eq!(1, round.current_player());
+        assert_eq!(12, round.talon().unwrap().len());
+        assert_eq!(1, round.next_dealer());
+    }
+
+    #[test]
+    fn drawer_wins_when_both_parties_reach_threshold_in_one_trick() {
+        let hands = two_players_round(1).hands().clone();
+        let talon = cards("9 c, Q c, K c, 9 d, J d, Q d, K d, 10 d, A d, J h, 10 h, A h");
+        let rules = Rules {
+            threshold: 14,
+            ..Rules::default()
+        };
+        let mut round = Round::from_deal(&Variant::TwoPlayers, 1, hands, talon, rules).unwrap();
+        // leader declares spades, drawer takes the queen with the ace
+        round.handle_move(PlayMove("Q s".parse().unwrap())).unwrap();
+        match round.handle_move(PlayMove("A s".parse().unwrap())).unwrap() {
+            RoundResult::Finished(result) => {
+                assert!(result.parties.iter().all(|party| party.points >= 14));
+                assert_eq!(vec![1], result.winners);
+            }
+            _ => panic!("Round should be finished"),
+        }
+    }
+
+    #[test]
+    fn round_in_play_keeps_closed_talon() {
+        let mut round = two_players_round(0);
+        round.handle_move(TalonMove(TalonAction::Close)).unwrap();
+        let mut resumed = Round::in_play(
+            Variant::TwoPlayers,
+            round.hands().clone(),
+            round.contract().clone(),
+            round.table().unwrap().clone(),
+            round.stashes().unwrap().clone(),
+            round.talon().cloned(),
+            *round.rules(),
+        );
+        assert_eq!(Some(1), resumed.talon().unwrap().closed_by());
+        assert_eq!(finish(&mut round, &[]), finish(&mut resumed, &[]));
+    }
+
+    #[test]
+    fn any_card_can_be_played_while_talon_is_open() {
+        let mut round = two_players_round(0);
+        let mut expected: Vec<_> = held(&round, 1).into_iter().map(PlayMove).collect();
+        expected.push(TalonMove(TalonAction::Close));
+        assert_eq!(expected, round.legal_moves());
+
+        round.handle_move(PlayMove(cards("A c")[0])).unwrap();
+        assert_eq!(6, round.legal_moves().len());
+    }
+
+    #[test]
+    fn suit_is_followed_after_closing() {
+        let mut round = two_players_round(0);
+        round.handle_move(TalonMove(TalonAction::Close)).unwrap();
+        assert_eq!(Some(1), round.talon().unwrap().closed_by());
+        round.handle_move(PlayMove(cards("A c")[0])).unwrap();
+        assert_eq!(vec![PlayMove(cards("J c")[0])], round.legal_moves());
+    }
+
+    #[test]
+    fn winner_of_trick_draws_first() {
+        let mut round = two_players_round(0);
+        round.handle_move(PlayMove(cards("Q h")[0])).unwrap();
+        round.handle_move(PlayMove(cards("9 s")[0])).unwrap();
+        assert_eq!(cards("A c, 10 c, K h, A s, 10 s, 9 c"), held(&round, 1));
+        assert_eq!(cards("J s, Q s, K s, 9 h, J c, Q c"), held(&round, 0));
+        assert_eq!(10, round.talon().unwrap().len());
+        // leading queen of triumph with its king declares 40
+        let stashes = round.stashes().unwrap();
+        assert_eq!(
+            43,
+            stashes
+                .stash(&Party::NonDealers)
+                .unwrap()
+                .points(Some(Suit::Heart), &Rules::default())
+        );
+    }
+
+    #[test]
+    fn nine_of_triumph_is_exchanged_by_leader() {
+        let mut round = two_players_round(1);
+        assert!(round
+            .legal_moves()
+            .contains(&TalonMove(TalonAction::ExchangeTriumph)));
+        round
+            .handle_move(TalonMove(TalonAction::ExchangeTriumph))
+            .unwrap();
+        assert!(held(&round, 0).contains(&cards("A h")[0]));
+        assert_eq!(
+            cards("9 h").first().copied(),
+            round.talon().unwrap().triumph_card()
+        );
+        assert_eq!(
+            Err(SechsUndSechzigError::TalonMoveNotAllowed),
+            round
+                .handle_move(TalonMove(TalonAction::ExchangeTriumph))
+                .map(|_| ())
+        );
+    }
+
+    #[test]
+    fn player_closing_talon_has_to_reach_threshold() {
+        let deal = || {
+            let hands = Hands::from_deal(
+                &Variant::TwoPlayers,
+                vec![
+                    cards("9 s, J s, 9 c, J c, 9 h, J h"),
+                    cards("Q s, K s, Q c, K c, 9 d, J d"),
+                ],
+            )
+            .unwrap();
+            let talon = cards("10 s, A s, 10 c, A c, Q d, K d, 10 d, A d, Q h, K h, 10 h, A h");
+            Round::from_deal(&Variant::TwoPlayers, 1, hands, talon, Rules::default()).unwrap()
+        };
+        let mut closed = deal();
+        closed.handle_move(TalonMove(TalonAction::Close)).unwrap();
+        let result = finish(&mut closed, &[]);
+        assert_eq!((vec![1], 2), (result.winners, result.base_points));
+
+        assert_eq!(1, finish(&mut deal(), &[]).base_points);
+    }
+
+    #[test]
+    fn two_players_need_whole_talon() {
+        let round = two_players_round(0);
+        let talon = round.talon().unwrap().cards();
+        let deal = |talon: Vec<Card>| {
+            Round::from_deal(
+                &Variant::TwoPlayers,
+                0,
+                round.hands().clone(),
+                talon,
+                Rules::default(),
+            )
+            .map(|_| ())
+        };
+        assert_eq!(
+            Err(SechsUndSechzigError::WrongTalonSize),
+            deal(talon[1..].to_vec())
+        );
+        let mut duplicated = talon;
+        duplicated[0] = held(&round, 0)[0];
+        assert_eq!(
+            Err(SechsUndSechzigError::DuplicatedCard(duplicated[0])),
+            deal(duplicated.clone())
+        );
+    }
+}
@@ -0,0 +1,350 @@
+//! House rules - values which differ between tables playing the game
+//!
+//! `Rules::default()` are Warsaw rules, which were the only ones before rules
+//! could be changed. Two-player games default to sixty-six rules instead, see
+//! `Rules::for_variant`. Other presets are looked up by name with `Rules::preset`.
+//!
+//! Rules are written as name of their preset, or as list of their values, like
+//! `target 100, misery-allowed no`. Values missing from the list are Warsaw ones.
+
+use std::{convert::TryInto, fmt, str::FromStr};
+
+use crate::{cards::Suit, error::SechsUndSechzigError, variant::Variant};
+
+/// Base points for winning each game type, before multiplier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PointTable {
+    pub non_triumph: i32,
+    /// Asking-about won by reaching threshold, when opponents took no points,
+    /// less than half of threshold and at least half of it
+    pub asking_about: [i32; 3],
+    /// Asking-about won by taking the last trick
+    pub asking_about_last_trick: i32,
+    pub looking_for: i32,
+    pub misery: i32,
+    pub shower: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rules {
+    /// Points team needs to win the game
+    pub target_score: i32,
+    /// Points which win asking-about before all cards are played, looking-for needs more
+    pub threshold: i32,
+    pub points: PointTable,
+    pub declaration: i32,
+    pub triumph_declaration: i32,
+    pub misery_allowed: bool,
+    pub shower_allowed: bool,
+    /// Raises allowed in one bidding, `None` when there is no limit
+    pub max_raises: Option<u32>,
+}
+
+impl Rules {
+    pub const PRESETS: [&'static str; 3] = ["warsaw", "silesian", "sixty-six"];
+
+    pub fn warsaw() -> Rules {
+        Rules {
+            target_score: 66,
+            threshold: 66,
+            points: PointTable {
+                non_triumph: 1,
+                asking_about: [3, 2, 1],
+                asking_about_last_trick: 1,
+                looking_for: 5,
+                misery: 7,
+                shower: 10,
+            },
+            declaration: 20,
+            triumph_declaration: 40,
+            misery_allowed: true,
+            shower_allowed: true,
+            max_raises: None,
+        }
+    }
+
+    /// Longer game without shower, in which looking-for and misery are worth more
+    /// and bidding can be raised at most twice
+    pub fn silesian() -> Rules {
+        Rules {
+            target_score: 99,
+            points: PointTable {
+                looking_for: 6,
+                misery: 8,
+                ..Rules::warsaw().points
+            },
+            shower_allowed: false,
+            max_raises: Some(2),
+            ..Rules::warsaw()
+        }
+    }
+
+    /// Classic two-player rules, in which game is played to seven points
+    pub fn sixty_six() -> Rules {
+        Rules {
+            target_score: 7,
+            ..Rules::warsaw()
+        }
+    }
+
+    /// Rules played by default in `variant`
+    pub fn for_variant(variant: Variant) -> Rules {
+        match variant {
+            Variant::TwoPlayers => Rules::sixty_six(),
+            _ => Rules::default(),
+        }
+    }
+
+    /// Rules given with `--rules RULES` command line argument, default ones for `variant`
+    /// without it, or message explaining how to give them
+    pub fn from_args(args: &[String], variant: Variant) -> Result<Rules, String> {
+        match args.iter().position(|arg| arg == "--rules") {
+            Some(index) => args
+                .get(index + 1)
+                .and_then(|rules| rules.parse().ok())
+                .ok_or_else(|| {
+                    format!(
+                        "--rules needs one of: {}, or values like \"target 100, misery 8\"",
+                        Rules::PRESETS.join(", ")
+                    )
+                }),
+            None => Ok(Rules::for_variant(variant)),
+        }
+    }
+
+    /// Preset with given name, ignoring case
+    pub fn preset(name: &str) -> Option<Rules> {
+        match &name.to_lowercase() as &str {
+            "warsaw" => Some(Rules::warsaw()),
+            "silesian" => Some(Rules::silesian()),
+            "sixty-six" => Some(Rules::sixty_six()),
+            _ => None,
+        }
+    }
+
+    /// Name of preset these rules are equal to
+    pub fn name(&self) -> Option<&'static str> {
+        Rules::PRESETS
+            .iter()
+            .copied()
+            .find(|name| Rules::preset(name).as_ref() == Some(self))
+    }
+
+    /// Whether contract with `multiplier` can be raised once more
+    pub fn can_raise(&self, multiplier: i32) -> bool {
+        self.max_raises
+            .is_none_or(|max_raises| multiplier < 1 << max_raises)
+    }
+
+    pub fn declaration_points(&self, suit: Suit, triumph: Option<Suit>) -> i32 {
+        if Some(suit) == triumph {
+            self.triumph_declaration
+        } else {
+            self.declaration
+        }
+    }
+
+    /// Points for asking-about won by reaching threshold, when opponents have `opponents_points`
+    pub fn asking_about_points(&self, opponents_points: i32) -> i32 {
+        let [whitewash, below_half, other] = self.points.asking_about;
+        match opponents_points {
+            0 => whitewash,
+            points if points < self.threshold / 2 => below_half,
+            _ => other,
+        }
+    }
+
+    /// Sets value written as `key value`, `None` when key is unknown or value cannot be parsed
+    fn set(&mut self, key: &str, value: &str) -> Option<()> {
+        let flag = || match value {
+            "yes" => Some(true),
+            "no" => Some(false),
+            _ => None,
+        };
+        match key {
+            "target" => self.target_score = value.parse().ok()?,
+            "threshold" => self.threshold = value.parse().ok()?,
+            "non-triumph" => self.points.non_triumph = value.parse().ok()?,
+            "asking-about" => {
+                let points: Vec<i32> = value
+                    .split('/')
+                    .map(|points| points.parse().ok())
+                    .collect::<Option<_>>()?;
+                self.points.asking_about = points.try_into().ok()?;
+            }
+            "asking-about-last-trick" => {
+                self.points.asking_about_last_trick = value.parse().ok()?
+            }
+            "looking-for" => self.points.looking_for = value.parse().ok()?,
+            "misery" => self.points.misery = value.parse().ok()?,
+            "shower" => self.points.shower = value.parse().ok()?,
+            "declaration" => self.declaration = value.parse().ok()?,
+            "triumph-declaration" => self.triumph_declaration = value.parse().ok()?,
+            "misery-allowed" => self.misery_allowed = flag()?,
+            "shower-allowed" => self.shower_allowed = flag()?,
+            "max-raises" => {
+                self.max_raises = match value {
+                    "none" => None,
+                    _ => Some(value.parse().ok()?),
+                }
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules::warsaw()
+    }
+}
+
+impl fmt::Display for Rules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(name) = self.name() {
+            return write!(f, "{}", name);
+        }
+        let flag = |allowed| if allowed { "yes" } else { "no" };
+        let points = &self.points;
+        let [whitewash, below_half, other] = points.asking_about;
+        write!(
+            f,
+            "target {}, threshold {}, non-triumph {}, asking-about {}/{}/{}, \
+             asking-about-last-trick {}, looking-for {}, misery {}, shower {}, \
+             declaration {}, triumph-declaration {}, misery-allowed {}, shower-allowed {}, ",
+            self.target_score,
+            self.threshold,
+            points.non_triumph,
+            whitewash,
+            below_half,
+            other,
+            points.asking_about_last_trick,
+            points.looking_for,
+            points.misery,
+            points.shower,
+            self.declaration,
+            self.triumph_declaration,
+            flag(self.misery_allowed),
+            flag(self.shower_allowed),
+        )?;
+        match self.max_raises {
+            Some(max_raises) => write!(f, "max-raises {}", max_raises),
+            None => write!(f, "max-raises none"),
+        }
+    }
+}
+
+impl FromStr for Rules {
+    type Err = SechsUndSechzigError;
+
+    /// Preset with given name, or Warsaw rules with listed values changed
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(rules) = Rules::preset(s.trim()) {
+            return Ok(rules);
+        }
+        let mut rules = Rules::default();
+        for value in s.split(',') {
+            value
+                .trim()
+                .split_once(' ')
+                .and_then(|(key, value)| rules.set(key, value.trim()))
+                .ok_or(SechsUndSechzigError::RulesParseError)?;
+        }
+        Ok(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_are_found_by_name() {
+        for name in Rules::PRESETS.iter() {
+            let rules = Rules::preset(name).unwrap();
+            assert_eq!(Some(*name), rules.name());
+        }
+        assert_eq!(Some(Rules::silesian()), Rules::preset("Silesian"));
+        assert_eq!(None, Rules::preset("cracow"));
+    }
+
+    #[test]
+    fn rules_are_written_and_parsed_back() {
+        let custom = Rules {
+            target_score: 100,
+            points: PointTable {
+                asking_about: [4, 2, 1],
+                ..Rules::warsaw().points
+            },
+            misery_allowed: false,
+            max_raises: Some(1),
+            ..Rules::warsaw()
+        };
+        for rules in [Rules::silesian(), Rules::sixty_six(), custom] {
+            assert_eq!(Ok(rules), rules.to_string().parse());
+        }
+        assert_eq!("silesian", Rules::silesian().to_string());
+        assert_eq!(Ok(Rules::silesian()), "Silesian".parse());
+        assert_eq!(
+            Ok(Rules {
+                target_score: 100,
+                shower_allowed: false,
+                ..Rules::warsaw()
+            }),
+            "target 100, shower-allowed no".parse()
+        );
+        for text in ["cracow", "target", "target many", "misery-allowed maybe"] {
+            assert_eq!(
+                Err(SechsUndSechzigError::RulesParseError),
+                text.parse::<Rules>()
+            );
+        }
+    }
+
+    #[test]
+    fn rules_are_read_from_args() {
+        let args =
+            |args: &[&str]| -> Vec<String> { args.iter().map(|arg| arg.to_string()).collect() };
+        assert_eq!(
+            Ok(Rules::sixty_six()),
+            Rules::from_args(&args(&["two"]), Variant::TwoPlayers)
+        );
+        assert_eq!(
+            Ok(Rules::silesian()),
+            Rules::from_args(
+                &args(&["--rules", "silesian", "--csv"]),
+                Variant::TwoPlayers
+            )
+        );
+        assert!(Rules::from_args(&args(&["--rules"]), Variant::ThreePlayers).is_err());
+        assert!(Rules::from_args(&args(&["--rules", "cracow"]), Variant::ThreePlayers).is_err());
+    }
+
+    #[test]
+    fn two_players_play_sixty_six_by_default() {
+        assert_eq!(Rules::sixty_six(), Rules::for_variant(Variant::TwoPlayers));
+        assert_eq!(Rules::warsaw(), Rules::for_variant(Variant::FourPlayers));
+    }
+
+    #[test]
+    fn raises_are_limited() {
+        let rules = Rules::silesian();
+        assert!(rules.can_raise(1));
+        assert!(rules.can_raise(2));
+        assert!(!rules.can_raise(4));
+        assert!(Rules::warsaw().can_raise(16));
+    }
+
+    #[test]
+    fn asking_about_is_worth_less_when_opponents_took_more() {
+        let rules = Rules::warsaw();
+        let points: Vec<_> = [0, 1, 32, 33, 60]
+            .iter()
+            .map(|points| rules.asking_about_points(*points))
+            .collect();
+        assert_eq!(vec![3, 2, 2, 1, 1], points);
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::prelude::*;
+use tbsux::{ismcts::Determinizer, playered::Player};
+
+use crate::{
+    cards::Card,
+    game::{SechsUndSechzig, SechsUndSechzigPlayerView, SechsUndSechzigState},
+    hands::Hands,
+    talon::PublicTalon,
+};
+
+/// Redeals cards a player cannot see, keeping everything visible in player's view
+///
+/// Cards on table and in stashes stay where they are, player keeps visible part
+/// of own hand (only first four cards during first stage of bidding), and
+/// remaining cards are shuffled between hidden parts of all hands and face-down
+/// cards of the talon, keeping their sizes.
+pub struct Sampler;
+
+impl Sampler {
+    /// Cards not visible in player's hand, not taken in tricks and not lying face up
+    pub fn unseen_cards(view: &SechsUndSechzigPlayerView) -> Vec<Card> {
+        let round = view.round();
+        let face_up = round.talon().and_then(PublicTalon::triumph_card);
+        let seen: HashSet<_> = view
+            .hand()
+            .iter()
+            .chain(round.table().into_iter().flat_map(|table| table.cards()))
+            .chain(
+                round
+                    .stashes()
+                    .into_iter()
+                    .flat_map(|stashes| stashes.cards()),
+            )
+            .chain(face_up.as_ref())
+            .collect();
+        Card::deck().filter(|card| !seen.contains(card)).collect()
+    }
+
+    pub fn sample_hands(view: &SechsUndSechzigPlayerView, rng: &mut dyn RngCore) -> Hands {
+        Sampler::sample_deal(view, rng).0
+    }
+
+    /// Sampled hands together with face-down cards of the talon, in order of drawing
+    ///
+    /// Players get no cards of suits they did not follow, and keep kings of
+    /// queens they declared.
+    pub fn sample_deal(
+        view: &SechsUndSechzigPlayerView,
+        rng: &mut dyn RngCore,
+    ) -> (Hands, Vec<Card>) {
+        let round = view.round();
+        let revealed = round.revealed();
+        let mut unseen = Sampler::unseen_cards(view);
+        unseen.shuffle(rng);
+
+        let mut hands: HashMap<Player, Vec<Card>> = HashMap::new();
+        // every seat is a place for one card, `None` is a face-down card of the talon
+        let mut seats = vec![];
+        for player in 0..round.variant().number_of_players() {
+            let cards = if player == view.player() {
+                view.hand().to_vec()
+            } else {
+                revealed
+                    .held(player)
+                    .filter(|card| unseen.contains(card))
+                    .collect()
+            };
+            unseen.retain(|card| !cards.contains(card));
+            let missing = round.hand_size(player).saturating_sub(cards.len());
+            seats.extend(std::iter::repeat_n(Some(player), missing));
+            hands.insert(player, cards);
+        }
+        seats.resize(seats.len().max(unseen.len()), None);
+
+        let can_hold = |card: &Card, seat: &Option<Player>| {
+            seat.is_none_or(|player| !revealed.is_void(player, card.suit))
+        };
+        // falls back to ignoring voids, when cards cannot be dealt consistently with them
+        let dealt = Sampler::match_seats(&unseen, &seats, can_hold, rng)
+            .unwrap_or_else(|| (0..unseen.len()).collect());
+
+        let mut stock = vec![];
+        for (card, seat) in unseen.iter().zip(dealt) {
+            match seats[seat] {
+                Some(player) => hands.entry(player).or_default().push(*card),
+                None => stock.push(*card),
+            }
+        }
+        (Hands::from_cards(hands), stock)
+    }
+
+    /// Seat for every card, such that cards are held only where allowed
+    ///
+    /// Cards take random free seats first and move others away only when they
+    /// have no free seat left, like in finding a matching with augmenting paths.
+    fn match_seats(
+        cards: &[Card],
+        seats: &[Option<Player>],
+        can_hold: impl Fn(&Card, &Option<Player>) -> bool,
+        rng: &mut dyn RngCore,
+    ) -> Option<Vec<usize>> {
+        fn place(
+            card: usize,
+            order: &[usize],
+            taken_by: &mut Vec<Option<usize>>,
+            visited: &mut Vec<bool>,
+            allowed: &dyn Fn(usize, usize) -> bool,
+        ) -> bool {
+            let free = order
+                .iter()
+                .find(|seat| taken_by[**seat].is_none() && allowed(card, **seat));
+            if let Some(seat) = free {
+                taken_by[*seat] = Some(card);
+                return true;
+            }
+            for &seat in order {
+                if visited[seat] || !allowed(card, seat) {
+                    continue;
+                }
+                visited[seat] = true;
+                if let Some(other) = taken_by[seat] {
+                    if place(other, order, taken_by, visited, allowed) {
+                        taken_by[seat] = Some(card);
+                        return true;
+                    }
+                }
+            }
+            false
+        }
+
+        let allowed = |card: usize, seat: usize| can_hold(&cards[card], &seats[seat]);
+        let mut taken_by = vec![None; seats.len()];
+        let mut order: Vec<_> = (0..seats.len()).collect();
+        for card in 0..cards.len() {
+            order.shuffle(rng);
+            let mut visited = vec![false; seats.len()];
+            if !place(card, &order, &mut taken_by, &mut visited, &allowed) {
+                return None;
+            }
+        }
+        let mut dealt = vec![0; cards.len()];
+        for (seat, card) in taken_by.iter().enumerate() {
+            if let Some(card) = card {
+                dealt[*card] = seat;
+            }
+        }
+        Some(dealt)
+    }
+}
+
+impl Determinizer<SechsUndSechzig> for Sampler {
+    fn determinize(
+        &self,
+        view: &SechsUndSechzigPlayerView,
+        rng: &mut dyn RngCore,
+    ) -> SechsUndSechzigState {
+        let (hands, stock) = Sampler::sample_deal(view, rng);
+        SechsUndSechzigState::from_round(
+            view.score().clone(),
+            view.round().with_hands(hands, stock),
+            rng.next_u64(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cards::Suit, variant::Variant};
+    use tbsux::{
+        agent::Agent,
+        ismcts::Ismcts,
+        mcts::{Budget, MctsConfig},
+        playered::View,
+        prelude::*,
+    };
+
+    fn states(variant: Variant, rng: &mut StdRng) -> Vec<SechsUndSechzigState> {
+        let mut state = SechsUndSechzig::with_random_seed(variant).initial_state();
+        let mut states = vec![];
+        while states.len() < 60 {
+            let moves = state.legal_moves();
+            match moves.choose(rng) {
+                Some(mv) => {
+                    states.push(state.clone());
+                    state = state.move_reducer(*mv).unwrap();
+                }
+                None => break,
+            }
+        }
+        states
+    }
+
+    fn view_of(state: &SechsUndSechzigState, player: Player) -> SechsUndSechzigPlayerView {
+        match state.progress_report() {
+            ProgressReport::InProgress(view) => view.player_view(player),
+            ProgressReport::Finished(_) => panic!("Game should be in progress"),
+        }
+    }
+
+    fn view(state: &SechsUndSechzigState) -> SechsUndSechzigPlayerView {
+        match state.progress_report() {
+            ProgressReport::InProgress(view) => view.player_view(view.current_player()),
+            ProgressReport::Finished(_) => panic!("Game should be in progress"),
+        }
+    }
+
+    #[test]
+    fn sampled_state_looks_same_for_player() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for variant in [
+            Variant::TwoPlayers,
+            Variant::ThreePlayers,
+            Variant::FourPlayers,
+        ] {
+            for state in states(variant, &mut rng) {
+                let view = view(&state);
+                let sampled = view_of(&Sampler.determinize(&view, &mut rng), view.player());
+                assert_eq!(view.hand(), sampled.hand());
+                assert_eq!(view.round().contract(), sampled.round().contract());
+                assert_eq!(view.round().table(), sampled.round().table());
+                assert_eq!(view.round().stashes(), sampled.round().stashes());
+                assert_eq!(view.round().talon(), sampled.round().talon());
+                for player in 0..variant.number_of_players() {
+                    assert_eq!(
+                        view.round().hand_size(player),
+                        sampled.round().hand_size(player)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sampled_hands_hold_every_unseen_card_once() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for state in states(Variant::FourPlayers, &mut rng) {
+            let view = view(&state);
+            let hands = Sampler::sample_hands(&view, &mut rng);
+            let mut dealt: Vec<_> = (0..4)
+                .flat_map(|player| hands.hand(&player).unwrap().full().copied())
+                .filter(|card| !view.hand().contains(card))
+                .collect();
+            let mut unseen = Sampler::unseen_cards(&view);
+            let key = |card: &Card| (card.suit, card.rank);
+            dealt.sort_by_key(key);
+            unseen.sort_by_key(key);
+            assert_eq!(unseen, dealt);
+        }
+    }
+
+    #[test]
+    fn samples_respect_what_players_revealed() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut constraints = 0;
+        for variant in [Variant::ThreePlayers, Variant::FourPlayers] {
+            for _ in 0..10 {
+                for state in states(variant, &mut rng) {
+                    let view = view(&state);
+                    let revealed = view.round().revealed();
+                    let unseen = Sampler::unseen_cards(&view);
+                    let hands = Sampler::sample_hands(&view, &mut rng);
+                    for player in (0..variant.number_of_players()).filter(|p| *p != view.player()) {
+                        let hand: Vec<_> = hands.hand(&player).unwrap().full().copied().collect();
+                        for suit in Suit::SUITS.iter() {
+                            if revealed.is_void(player, *suit) {
+                                constraints += 1;
+                                assert!(hand.iter().all(|card| card.suit != *suit));
+                            }
+                        }
+                        for king in revealed.held(player).filter(|card| unseen.contains(card)) {
+                            constraints += 1;
+                            assert!(hand.contains(&king));
+                        }
+                    }
+                }
+            }
+        }
+        assert!(constraints > 0);
+    }
+
+    #[test]
+    fn talon_is_sampled_from_unseen_cards() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for state in states(Variant::TwoPlayers, &mut rng) {
+            let view = view(&state);
+            let talon = view.round().talon().unwrap();
+            let (hands, stock) = Sampler::sample_deal(&view, &mut rng);
+            let sampled = state.round().public().with_hands(hands, stock);
+            let cards = sampled.talon().unwrap().cards();
+            assert_eq!(talon.len(), cards.len());
+            assert_eq!(talon.triumph_card(), cards.last().copied());
+        }
+    }
+
+    #[test]
+    fn first_stage_hides_rest_of_own_hand() {
+        let state = SechsUndSechzig::with_random_seed(Variant::ThreePlayers).initial_state();
+        let view = view(&state);
+        assert_eq!(4, view.hand().len());
+        assert_eq!(20, Sampler::unseen_cards(&view).len());
+    }
+
+    #[test]
+    fn ismcts_picks_legal_moves() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut agent = Ismcts::with_seed(
+            MctsConfig {
+                budget: Budget::Iterations(10),
+                ..Default::default()
+            },
+            Sampler,
+            2,
+        );
+        for state in states(Variant::ThreePlayers, &mut rng).iter().step_by(10) {
+            let legal_moves = state.legal_moves();
+            let mv = agent.select_move(&view(state), &legal_moves).unwrap();
+            assert!(legal_moves.contains(&mv));
+        }
+    }
+}
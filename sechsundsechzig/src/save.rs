@@ -0,0 +1,366 @@
+//! Saving games in progress to text files
+//!
+//! Save stores game's variant, seed, house rules when they are not the default
+//! ones, and every move played, so loading it replays the game and restores the
+//! whole state, including position of random number generator. First line holds
+//! version of format, so saves written by older versions can still be read.
+//!
+//! Since version 2, save can name its house rules, every move finishing a round
+//! is followed by its result and save ends with score of every team. Loading
+//! checks them against the replayed game, so changes in rules of the engine are
+//! reported instead of silently changing saved games. Version 1 saves are
+//! replayed as they are, unless they contain raised contracts, whose points
+//! were not multiplied then.
+
+use std::{
+    error, fmt, fs,
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+};
+
+use tbsux::prelude::*;
+
+use crate::{
+    error::SechsUndSechzigError,
+    game::{SechsUndSechzig, SechsUndSechzigState},
+    round::FinishedRound,
+    rules::Rules,
+    team::Team,
+    variant::Variant,
+};
+
+const HEADER: &str = "sechsundsechzig";
+pub const VERSION: u32 = 2;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    UnsupportedVersion(String),
+    /// Line which could not be parsed, counting from 1, or 0 when save ends too early
+    Malformed(usize),
+    /// Move which could not be replayed, counting from 0
+    IllegalMove(usize, SechsUndSechzigError),
+    /// Line with result or score different in replayed game, counting from 1
+    StateMismatch(usize),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl error::Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(err: io::Error) -> SaveError {
+        SaveError::Io(err)
+    }
+}
+
+pub fn write_save(state: &SechsUndSechzigState, writer: &mut impl Write) -> io::Result<()> {
+    writeln!(writer, "{} {}", HEADER, VERSION)?;
+    let variant = match state.variant() {
+        Variant::TwoPlayers => "two",
+        Variant::ThreePlayers => "three",
+        Variant::FourPlayers => "four",
+    };
+    writeln!(writer, "variant {}", variant)?;
+    writeln!(writer, "seed {}", state.seed())?;
+    if *state.rules() != Rules::for_variant(state.variant()) {
+        // saves only name the rules, so custom ones could not be loaded back
+        let name = state.rules().name().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Custom rules cannot be saved")
+        })?;
+        writeln!(writer, "rules {}", name)?;
+    }
+    let mut replayed = state.game().initial_state();
+    for mv in state.history() {
+        writeln!(writer, "{}", mv)?;
+        let finished = replayed.results().len();
+        replayed = replayed
+            .move_reducer(mv)
+            .expect("History of a game contains legal moves");
+        if let Some(result) = replayed.results().get(finished) {
+            writeln!(writer, "result {}", result_line(result))?;
+        }
+    }
+    writeln!(writer, "score {}", score_line(state))?;
+    Ok(())
+}
+
+fn result_line(result: &FinishedRound) -> String {
+    let winners: Vec<_> = result
+        .winners
+        .iter()
+        .map(|player| player.to_string())
+        .collect();
+    format!("{} {}", result.points(), winners.join(","))
+}
+
+/// Points of every team, in order of teams
+fn score_line(state: &SechsUndSechzigState) -> String {
+    let points: Vec<_> = Team::teams(state.variant())
+        .map(|team| state.score().team_points(&team).unwrap_or(0).to_string())
+        .collect();
+    points.join(" ")
+}
+
+pub fn read_save(reader: impl BufRead) -> Result<SechsUndSechzigState, SaveError> {
+    let mut lines = reader
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .peekable();
+
+    let (_, header) = next_line(&mut lines)?;
+    let version: u32 = match header.split_once(' ') {
+        Some((HEADER, version)) => match version.parse() {
+            Ok(version) if (1..=VERSION).contains(&version) => version,
+            _ => return Err(SaveError::UnsupportedVersion(header)),
+        },
+        _ => return Err(SaveError::UnsupportedVersion(header)),
+    };
+
+    let (number, line) = next_line(&mut lines)?;
+    let variant = match line.as_str() {
+        "variant two" => Variant::TwoPlayers,
+        "variant three" => Variant::ThreePlayers,
+        "variant four" => Variant::FourPlayers,
+        _ => return Err(SaveError::Malformed(number)),
+    };
+
+    let (number, line) = next_line(&mut lines)?;
+    let seed = line
+        .strip_prefix("seed ")
+        .and_then(|seed| seed.parse().ok())
+        .ok_or(SaveError::Malformed(number))?;
+
+    let mut rules = Rules::for_variant(variant);
+    if let Some((number, Ok(line))) = lines.peek().filter(|_| version >= 2) {
+        if let Some(name) = line.strip_prefix("rules ") {
+            rules = Rules::preset(name).ok_or(SaveError::Malformed(*number))?;
+            lines.next();
+        }
+    }
+
+    let mut state = SechsUndSechzig::with_seed(variant, seed)
+        .with_rules(rules)
+        .initial_state();
+    let mut moves = 0;
+    let mut checked_results = 0;
+    let mut checked_score = false;
+    for (number, line) in lines {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if version >= 2 {
+            if let Some(result) = line.strip_prefix("result ") {
+                match state.results().get(checked_results) {
+                    Some(replayed) if result_line(replayed) == result => checked_results += 1,
+                    _ => return Err(SaveError::StateMismatch(number)),
+                }
+                continue;
+            }
+            if let Some(score) = line.strip_prefix("score ") {
+                if score != score_line(&state) {
+                    return Err(SaveError::StateMismatch(number));
+                }
+                checked_score = true;
+                continue;
+            }
+        }
+        if checked_score {
+            return Err(SaveError::Malformed(number));
+        }
+        let mv = line.parse().map_err(|_| SaveError::Malformed(number))?;
+        let finished = state.results().len();
+        state = state
+            .move_reducer(mv)
+            .map_err(|err| SaveError::IllegalMove(moves, err))?;
+        moves += 1;
+        // version 1 did not multiply points of raised contracts
+        if version == 1
+            && state.results()[finished..]
+                .iter()
+                .any(|r| r.contract.multiplier > 1)
+        {
+            return Err(SaveError::StateMismatch(number));
+        }
+    }
+    if version >= 2 && (!checked_score || checked_results != state.results().len()) {
+        return Err(SaveError::Malformed(0));
+    }
+    Ok(state)
+}
+
+fn next_line(
+    lines: &mut impl Iterator<Item = (usize, io::Result<String>)>,
+) -> Result<(usize, String), SaveError> {
+    match lines.next() {
+        Some((number, line)) => Ok((number, line?)),
+        None => Err(SaveError::Malformed(0)),
+    }
+}
+
+pub fn save_to_file(state: &SechsUndSechzigState, path: impl AsRef<Path>) -> io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    write_save(state, &mut file)
+}
+
+pub fn load_from_file(path: impl AsRef<Path>) -> Result<SechsUndSechzigState, SaveError> {
+    read_save(BufReader::new(fs::File::open(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bidding::Bid, sus_move::SusMove};
+
+    fn played_state(moves: usize) -> SechsUndSechzigState {
+        let mut state = SechsUndSechzig::with_seed(Variant::ThreePlayers, 3).initial_state();
+        for _ in 0..moves {
+            state = state.move_reducer(state.legal_moves()[0]).unwrap();
+        }
+        state
+    }
+
+    #[test]
+    fn loaded_game_continues_where_it_was_saved() {
+        let state = played_state(40);
+        let mut saved = vec![];
+        write_save(&state, &mut saved).unwrap();
+
+        let loaded = read_save(saved.as_slice()).unwrap();
+        assert_eq!(state.history(), loaded.history());
+        assert_eq!(state.legal_moves(), loaded.legal_moves());
+        let next = state.legal_moves()[0];
+        assert_eq!(
+            state.move_reducer(next).unwrap().legal_moves(),
+            loaded.move_reducer(next).unwrap().legal_moves()
+        );
+    }
+
+    #[test]
+    fn rules_are_saved_by_name() {
+        let game =
+            SechsUndSechzig::with_seed(Variant::ThreePlayers, 3).with_rules(Rules::silesian());
+        let mut state = game.initial_state();
+        for _ in 0..10 {
+            state = state.move_reducer(state.legal_moves()[0]).unwrap();
+        }
+        let mut saved = vec![];
+        write_save(&state, &mut saved).unwrap();
+        assert!(String::from_utf8_lossy(&saved).contains("rules silesian\n"));
+
+        let loaded = read_save(saved.as_slice()).unwrap();
+        assert_eq!(Rules::silesian(), *loaded.rules());
+        assert_eq!(state.history(), loaded.history());
+
+        for saved in [
+            "sechsundsechzig 2\nvariant three\nseed 3\nrules cracow\n",
+            "sechsundsechzig 1\nvariant three\nseed 3\nrules silesian\n",
+        ] {
+            assert!(matches!(
+                read_save(saved.as_bytes()),
+                Err(SaveError::Malformed(4))
+            ));
+        }
+    }
+
+    #[test]
+    fn replayed_results_and_score_are_checked() {
+        let state = played_state(120);
+        assert!(!state.results().is_empty());
+        let mut saved = vec![];
+        write_save(&state, &mut saved).unwrap();
+        let saved = String::from_utf8(saved).unwrap();
+        assert!(saved.contains("\nresult "));
+
+        let lines: Vec<_> = saved.lines().collect();
+        let score = lines.len() - 1;
+        let mut changed = lines.clone();
+        changed[score] = "score 66 0 0";
+        assert!(matches!(
+            read_save(changed.join("\n").as_bytes()),
+            Err(SaveError::StateMismatch(number)) if number == score + 1
+        ));
+        let without_score = lines[..score].join("\n");
+        assert!(matches!(
+            read_save(without_score.as_bytes()),
+            Err(SaveError::Malformed(0))
+        ));
+    }
+
+    #[test]
+    fn version_one_saves_are_replayed_unless_contracts_were_raised() {
+        let to_version_one = |state: &SechsUndSechzigState| {
+            let mut saved = vec![];
+            write_save(state, &mut saved).unwrap();
+            let saved = String::from_utf8(saved).unwrap();
+            let lines: Vec<_> = saved
+                .lines()
+                .skip(1)
+                .filter(|line| !line.starts_with("result ") && !line.starts_with("score "))
+                .collect();
+            format!("sechsundsechzig 1\n{}\n", lines.join("\n"))
+        };
+        let state = played_state(120);
+        assert!(state
+            .results()
+            .iter()
+            .all(|result| result.contract.multiplier == 1));
+        let loaded = read_save(to_version_one(&state).as_bytes()).unwrap();
+        assert_eq!(state.history(), loaded.history());
+        assert_eq!(state.score(), loaded.score());
+
+        let mut state = SechsUndSechzig::with_seed(Variant::ThreePlayers, 3).initial_state();
+        while state
+            .results()
+            .iter()
+            .all(|result| result.contract.multiplier == 1)
+        {
+            let moves = state.legal_moves();
+            let raise = SusMove::BiddingMove(Bid::Raise);
+            let mv = if moves.contains(&raise) {
+                raise
+            } else {
+                moves[0]
+            };
+            state = state.move_reducer(mv).unwrap();
+        }
+        assert!(matches!(
+            read_save(to_version_one(&state).as_bytes()),
+            Err(SaveError::StateMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_version() {
+        let saved = "sechsundsechzig 999\nvariant three\nseed 3\n";
+        assert!(matches!(
+            read_save(saved.as_bytes()),
+            Err(SaveError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn reports_line_that_cannot_be_parsed() {
+        let saved = "sechsundsechzig 1\nvariant five\nseed 3\n";
+        assert!(matches!(
+            read_save(saved.as_bytes()),
+            Err(SaveError::Malformed(2))
+        ));
+    }
+
+    #[test]
+    fn reports_move_that_cannot_be_replayed() {
+        let saved = "sechsundsechzig 1\nvariant three\nseed 3\nA ♠\n";
+        assert!(matches!(
+            read_save(saved.as_bytes()),
+            Err(SaveError::IllegalMove(0, _))
+        ));
+    }
+}
@@ -1,28 +1,43 @@
 use core::fmt;
 use std::collections::HashMap;
 
+use tbsux::playered::{Outcome, Player};
+
 use crate::{
     error::{SechsUndSechzigError, SusResult},
+    rules::Rules,
     team::Team,
     variant::Variant,
 };
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Score {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_pairs"))]
     scores: HashMap<Team, i32>,
+    target: i32,
 }
 
 impl Score {
-    const MAX_POINTS: i32 = 66;
-
+    /// Score of a game played to target of default rules of `variant`
     pub fn empty(variant: Variant) -> Score {
+        Score::with_target(variant, Rules::for_variant(variant).target_score)
+    }
+
+    /// Score of a game won by team which gets `target` points first
+    pub fn with_target(variant: Variant, target: i32) -> Score {
         let teams = Team::teams(variant);
         Score {
             scores: teams.map(|t| (t, 0)).collect(),
+            target,
         }
     }
 
+    pub fn target(&self) -> i32 {
+        self.target
+    }
+
     pub fn add_points(&mut self, team: &Team, points: i32) -> SusResult<()> {
-        if let Some(elem) = self.scores.get_mut(&team) {
+        if let Some(elem) = self.scores.get_mut(team) {
             *elem += points;
             Ok(())
         } else {
@@ -30,22 +45,51 @@ impl Score {
         }
     }
 
+    pub fn team_points(&self, team: &Team) -> Option<i32> {
+        self.scores.get(team).copied()
+    }
+
     pub fn winner(&self) -> Option<&Team> {
         self.scores
             .iter()
-            .filter(|(_, score)| **score >= Score::MAX_POINTS)
-            .max_by_key(|(_, score)| **score)
+            .filter(|(_, score)| **score >= self.target)
+            .max_by_key(|(Team(id, _), score)| (**score, std::cmp::Reverse(*id)))
             .map(|(team, _)| team)
     }
 }
 
+impl Outcome for Score {
+    fn payoff(&self, player: Player) -> f64 {
+        let won = self
+            .winner()
+            .and_then(|team| team.players().ok())
+            .is_some_and(|mut players| players.any(|p| p == player));
+        if won {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Points of player's team
+    fn points(&self, player: Player) -> f64 {
+        self.scores
+            .iter()
+            .find(|(team, _)| {
+                team.players()
+                    .is_ok_and(|mut players| players.any(|p| p == player))
+            })
+            .map_or(0.0, |(_, points)| *points as f64)
+    }
+}
+
 impl fmt::Display for Score {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let winner = self.winner();
         let mut scores: Vec<_> = self.scores.iter().collect();
         scores.sort_by_key(|(Team(id, _), _)| id);
         for (team, score) in scores {
-            let tag = if winner == Some(&team) {
+            let tag = if winner == Some(team) {
                 " [winner]"
             } else {
                 ""
@@ -86,7 +130,7 @@ mod tests {
     fn returns_winner() {
         let mut scores = Score::empty(Variant::ThreePlayers);
         let winner = Team::teams(Variant::ThreePlayers).next().unwrap();
-        scores.add_points(&winner, Score::MAX_POINTS).unwrap();
+        scores.add_points(&winner, scores.target()).unwrap();
 
         assert_eq!(Some(&winner), scores.winner());
     }
@@ -96,12 +140,33 @@ mod tests {
         let mut scores = Score::empty(Variant::ThreePlayers);
         let almost_winner = Team::teams(Variant::ThreePlayers).next().unwrap();
         let winner = Team::teams(Variant::ThreePlayers).next().unwrap();
+        scores.add_points(&almost_winner, scores.target()).unwrap();
+        scores.add_points(&winner, scores.target() + 1).unwrap();
+
+        assert_eq!(Some(&winner), scores.winner());
+    }
+
+    #[test]
+    fn winner_needs_target_points() {
+        let mut scores = Score::with_target(Variant::ThreePlayers, 99);
+        let team = Team::teams(Variant::ThreePlayers).next().unwrap();
+        scores.add_points(&team, 66).unwrap();
+        assert_eq!(None, scores.winner());
+        scores.add_points(&team, 33).unwrap();
+        assert_eq!(Some(&team), scores.winner());
+    }
+
+    #[test]
+    fn winning_team_players_get_payoff() {
+        let mut scores = Score::empty(Variant::FourPlayers);
         scores
-            .add_points(&almost_winner, Score::MAX_POINTS)
+            .add_points(&Team(1, Variant::FourPlayers), scores.target())
             .unwrap();
-        scores.add_points(&winner, Score::MAX_POINTS + 1).unwrap();
 
-        assert_eq!(Some(&winner), scores.winner());
+        assert_eq!(0.0, scores.payoff(0));
+        assert_eq!(1.0, scores.payoff(1));
+        assert_eq!(0.0, scores.payoff(2));
+        assert_eq!(1.0, scores.payoff(3));
     }
 
     #[test]
@@ -110,4 +175,26 @@ mod tests {
 
         assert_eq!(None, scores.winner());
     }
+
+    #[test]
+    fn players_get_points_of_their_team() {
+        let mut scores = Score::empty(Variant::FourPlayers);
+        scores
+            .add_points(&Team(1, Variant::FourPlayers), 12)
+            .unwrap();
+        assert_eq!(Some(12), scores.team_points(&Team(1, Variant::FourPlayers)));
+        assert_eq!(12.0, scores.points(3));
+        assert_eq!(0.0, scores.points(2));
+    }
+
+    #[test]
+    fn tie_is_won_by_team_with_lower_id() {
+        let mut scores = Score::empty(Variant::ThreePlayers);
+        for id in [2, 1] {
+            scores
+                .add_points(&Team(id, Variant::ThreePlayers), scores.target())
+                .unwrap();
+        }
+        assert_eq!(Some(&Team(1, Variant::ThreePlayers)), scores.winner());
+    }
 }
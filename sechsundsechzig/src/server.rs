@@ -0,0 +1,430 @@
+//! Hosting a game over TCP, with every player connected from a separate client
+//!
+//! Protocol is line based. After connecting, server sends `WELCOME <players>`
+//! and client claims a seat with `SEAT <player>`, or just `SEAT` for any free
+//! one, which server confirms with `SEATED <player>` or refuses with
+//! `ERROR <reason>`. Clients which do not claim a seat within `SEAT_TIMEOUT`
+//! are disconnected. Seats are claimed one connection at a time, so a silent
+//! client still delays clients connecting after it by up to `SEAT_TIMEOUT`.
+//! Once every seat is taken, before each move every client gets own player's
+//! view between `VIEW` and `END` lines, followed by `TURN <player>`. Player to
+//! move sends a move in the same syntax as in terminal, other ones only get
+//! `ERROR` back. Accepted moves are announced with `PLAYED <player> <move>`,
+//! and after the last one server sends `FINISHED`, final score and `END`.
+//!
+//! Games served with a time control also send `CLOCK <milliseconds>...` with
+//! time left to every player before each `TURN`. When player to move runs out
+//! of time, server announces `TIMEOUT <player>` and either ends the game with
+//! `FORFEITED <player>` or plays a move for them.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::mpsc::{channel, RecvTimeoutError, Sender},
+    thread,
+    time::Duration,
+};
+
+use tbsux::{
+    agent::{Agent, RandomAgent},
+    clock::{PlayerClocks, SystemClock, TimeControl, TimedResult, TimeoutAction},
+    playered::{Game as _, Player, View},
+    prelude::*,
+};
+
+use crate::{game::SechsUndSechzig, score::Score, sus_move::SusMove};
+
+/// Time client has to claim a seat after connecting
+pub const SEAT_TIMEOUT: Duration = Duration::from_secs(5);
+
+enum Event {
+    Line(Player, String),
+    Left(Player),
+}
+
+/// Waits until every seat is taken by a client connecting to `listener`, then plays the game
+///
+/// Fails when any of seated players disconnects before the game ends.
+pub fn serve(listener: &TcpListener, game: SechsUndSechzig) -> io::Result<Score> {
+    match host(listener, game, None)? {
+        TimedResult::Finished(score) => Ok(score),
+        TimedResult::Forfeited(_) => unreachable!("Game without time control cannot be forfeited"),
+    }
+}
+
+/// Serves the game like [`serve`], giving every player limited time for their moves
+///
+/// Player running out of time is handled according to `on_timeout`, so games do
+/// not stall when somebody walks away from their client.
+pub fn serve_timed(
+    listener: &TcpListener,
+    game: SechsUndSechzig,
+    control: TimeControl,
+    on_timeout: TimeoutAction<SechsUndSechzig>,
+) -> io::Result<TimedResult<Score>> {
+    host(listener, game, Some((control, on_timeout)))
+}
+
+fn host(
+    listener: &TcpListener,
+    game: SechsUndSechzig,
+    time_control: Option<(TimeControl, TimeoutAction<SechsUndSechzig>)>,
+) -> io::Result<TimedResult<Score>> {
+    let players = game.no_of_players();
+    let mut seats: Vec<Option<TcpStream>> = (0..players).map(|_| None).collect();
+    let (sender, receiver) = channel();
+
+    while seats.iter().any(Option::is_none) {
+        let (stream, _) = listener.accept()?;
+        // moves are short lines waiting for an answer, sending them immediately avoids delays
+        stream.set_nodelay(true)?;
+        // client which failed to claim a seat is dropped, waiting for another one
+        if let Ok((player, reader)) = claim_seat(stream, &mut seats) {
+            listen(player, reader, sender.clone());
+        }
+    }
+
+    let (mut clocks, mut on_timeout) = match time_control {
+        Some((control, on_timeout)) => (
+            Some(PlayerClocks::new(SystemClock::new(), control, players)),
+            on_timeout,
+        ),
+        None => (None, TimeoutAction::Forfeit),
+    };
+    let mut random = None;
+    let mut state = game.initial_state();
+    loop {
+        let view = match state.progress_report() {
+            ProgressReport::Finished(score) => {
+                broadcast(&mut seats, &format!("FINISHED\n{}END", score));
+                return Ok(TimedResult::Finished(score));
+            }
+            ProgressReport::InProgress(view) => view,
+        };
+        let current = view.current_player();
+        let clock_line = clocks.as_ref().map(|clocks| {
+            let remaining: Vec<_> = (0..players)
+                .map(|player| clocks.remaining(player).as_millis().to_string())
+                .collect();
+            format!("CLOCK {}\n", remaining.join(" "))
+        });
+        for player in 0..players {
+            let message = format!(
+                "VIEW\n{}\nEND\n{}TURN {}",
+                view.player_view(player),
+                clock_line.as_deref().unwrap_or(""),
+                current
+            );
+            send(&mut seats, player, &message);
+        }
+        if let Some(clocks) = &mut clocks {
+            clocks.start(current);
+        }
+
+        state = loop {
+            let event = match &clocks {
+                Some(clocks) => receiver.recv_timeout(clocks.remaining(current)),
+                None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            let (player, line) = match event {
+                Ok(Event::Line(player, line)) => (player, line),
+                Ok(Event::Left(player)) => {
+                    let message = format!("player {} disconnected", player);
+                    broadcast(&mut seats, &format!("ERROR {}", message));
+                    return Err(io::Error::new(io::ErrorKind::ConnectionAborted, message));
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    if let Some(clocks) = &mut clocks {
+                        clocks.stop();
+                    }
+                    broadcast(&mut seats, &format!("TIMEOUT {}", current));
+                    let player_view = view.player_view(current);
+                    let legal_moves = state.legal_moves();
+                    let mv = match &mut on_timeout {
+                        TimeoutAction::Forfeit => {
+                            broadcast(&mut seats, &format!("FORFEITED {}", current));
+                            return Ok(TimedResult::Forfeited(current));
+                        }
+                        TimeoutAction::RandomMove(seed) => {
+                            let random =
+                                random.get_or_insert_with(|| RandomAgent::with_seed(*seed));
+                            Agent::<SechsUndSechzig>::select_move(
+                                random,
+                                &player_view,
+                                &legal_moves,
+                            )
+                        }
+                        TimeoutAction::Bot(agent) => agent.select_move(&player_view, &legal_moves),
+                    };
+                    let mv = mv.expect("Game in progress has legal moves");
+                    broadcast(&mut seats, &format!("PLAYED {} {}", current, mv));
+                    break state
+                        .move_reducer(mv)
+                        .expect("Substitute move is one of legal moves");
+                }
+                Err(RecvTimeoutError::Disconnected) => unreachable!("Server keeps a sender"),
+            };
+            if player != current {
+                send(&mut seats, player, "ERROR not your turn");
+                continue;
+            }
+            let mv: SusMove = match line.trim().parse() {
+                Ok(mv) => mv,
+                Err(_) => {
+                    send(&mut seats, player, "ERROR could not parse move");
+                    continue;
+                }
+            };
+            match state.move_reducer(mv) {
+                Ok(new_state) => {
+                    if let Some(clocks) = &mut clocks {
+                        clocks.stop();
+                    }
+                    broadcast(&mut seats, &format!("PLAYED {} {}", player, mv));
+                    break new_state;
+                }
+                Err(err) => send(&mut seats, player, &format!("ERROR {}", err)),
+            }
+        };
+    }
+}
+
+fn claim_seat(
+    mut stream: TcpStream,
+    seats: &mut [Option<TcpStream>],
+) -> io::Result<(Player, BufReader<TcpStream>)> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    writeln!(stream, "WELCOME {}", seats.len())?;
+    let mut line = String::new();
+    // seats are claimed one by one, so a silent client would block everyone else
+    stream.set_read_timeout(Some(SEAT_TIMEOUT))?;
+    reader.read_line(&mut line)?;
+    stream.set_read_timeout(None)?;
+
+    let mut words = line.split_whitespace();
+    let requested = match (words.next(), words.next()) {
+        (Some("SEAT"), None) => seats.iter().position(Option::is_none),
+        (Some("SEAT"), Some(player)) => player
+            .parse()
+            .ok()
+            .filter(|player: &usize| seats.get(*player).is_some_and(Option::is_none)),
+        _ => None,
+    };
+    match requested {
+        Some(player) => {
+            writeln!(stream, "SEATED {}", player)?;
+            seats[player] = Some(stream);
+            Ok((player as Player, reader))
+        }
+        None => {
+            writeln!(stream, "ERROR seat is not available")?;
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seat is not available",
+            ))
+        }
+    }
+}
+
+/// Forwards lines sent by client of `player` to server's event queue
+fn listen(player: Player, reader: BufReader<TcpStream>, sender: Sender<Event>) {
+    thread::spawn(move || {
+        for line in reader.lines() {
+            match line {
+                Ok(line) => {
+                    if sender.send(Event::Line(player, line)).is_err() {
+                        return;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+        let _ = sender.send(Event::Left(player));
+    });
+}
+
+/// Writes a message to client of `player`, disconnected clients are reported by their listeners
+fn send(seats: &mut [Option<TcpStream>], player: Player, message: &str) {
+    if let Some(Some(stream)) = seats.get_mut(player as usize) {
+        let _ = writeln!(stream, "{}", message);
+    }
+}
+
+fn broadcast(seats: &mut [Option<TcpStream>], message: &str) {
+    for player in 0..seats.len() {
+        send(seats, player as Player, message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+    use crate::variant::Variant;
+
+    struct Client {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    }
+
+    impl Client {
+        fn connect(address: &str, seat: &str) -> Client {
+            let writer = TcpStream::connect(address).unwrap();
+            writer.set_nodelay(true).unwrap();
+            // longer than seat timeout, as earlier silent clients delay welcome
+            writer.set_read_timeout(Some(SEAT_TIMEOUT * 2)).unwrap();
+            let mut client = Client {
+                reader: BufReader::new(writer.try_clone().unwrap()),
+                writer,
+            };
+            assert!(client.read_line().starts_with("WELCOME"));
+            client.send(seat);
+            client
+        }
+
+        fn read_line(&mut self) -> String {
+            let mut line = String::new();
+            self.reader.read_line(&mut line).unwrap();
+            line.trim_end().to_owned()
+        }
+
+        fn send(&mut self, line: &str) {
+            writeln!(self.writer, "{}", line).unwrap();
+        }
+
+        /// Reads messages until next turn, returning shown view and player to move
+        fn read_turn(&mut self) -> Option<(String, Player)> {
+            let mut view = vec![];
+            loop {
+                let line = self.read_line();
+                if line == "FINISHED" {
+                    return None;
+                } else if line == "VIEW" {
+                    view.clear();
+                    loop {
+                        match self.read_line().as_str() {
+                            "END" => break,
+                            line => view.push(line.to_owned()),
+                        }
+                    }
+                } else if let Some(player) = line.strip_prefix("TURN ") {
+                    return Some((view.join("\n"), player.parse().unwrap()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn plays_whole_game_over_localhost() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let game = SechsUndSechzig::with_seed(Variant::ThreePlayers, 7);
+        let server = thread::spawn(move || serve(&listener, game));
+
+        let mut clients = vec![Client::connect(&address, "SEAT 1")];
+        assert_eq!("SEATED 1", clients[0].read_line());
+        let mut taken = Client::connect(&address, "SEAT 1");
+        assert_eq!("ERROR seat is not available", taken.read_line());
+        for expected in ["SEATED 0", "SEATED 2"] {
+            let mut client = Client::connect(&address, "SEAT");
+            assert_eq!(expected, client.read_line());
+            clients.push(client);
+        }
+        clients.swap(0, 1);
+
+        // mirror of server's game, used to pick legal moves and check views
+        let mut state = SechsUndSechzig::with_seed(Variant::ThreePlayers, 7).initial_state();
+        let mut refused_out_of_turn = false;
+        loop {
+            let turns: Vec<_> = clients
+                .iter_mut()
+                .map(|client| client.read_turn())
+                .collect();
+            let view = match state.progress_report() {
+                ProgressReport::InProgress(view) => view,
+                ProgressReport::Finished(_) => {
+                    assert!(turns.iter().all(Option::is_none));
+                    break;
+                }
+            };
+            let current = view.current_player();
+            for (player, turn) in turns.into_iter().enumerate() {
+                let (shown, turn) = turn.unwrap();
+                assert_eq!(current, turn);
+                assert_eq!(view.player_view(player as Player).to_string(), shown);
+            }
+
+            if !refused_out_of_turn {
+                let other = (current as usize + 1) % clients.len();
+                clients[other].send("pass");
+                assert_eq!("ERROR not your turn", clients[other].read_line());
+                refused_out_of_turn = true;
+            }
+            let mv = state.legal_moves()[0];
+            clients[current as usize].send(&mv.to_string());
+            state = state.move_reducer(mv).unwrap();
+        }
+
+        let score = server.join().unwrap().unwrap();
+        assert!(score.winner().is_some());
+    }
+
+    #[test]
+    fn silent_client_does_not_block_seating() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let game = SechsUndSechzig::with_seed(Variant::ThreePlayers, 7);
+        let control = TimeControl {
+            initial: Duration::from_millis(100),
+            increment: Duration::ZERO,
+        };
+        let server =
+            thread::spawn(move || serve_timed(&listener, game, control, TimeoutAction::Forfeit));
+
+        let mut silent = TcpStream::connect(&address).unwrap();
+        let mut clients: Vec<_> = (0..3).map(|_| Client::connect(&address, "SEAT")).collect();
+        for (player, client) in clients.iter_mut().enumerate() {
+            assert_eq!(format!("SEATED {}", player), client.read_line());
+        }
+        // silent client got welcomed and was disconnected after timeout
+        let mut dropped = String::new();
+        silent.read_to_string(&mut dropped).unwrap();
+        assert!(dropped.starts_with("WELCOME"));
+        assert!(matches!(
+            server.join().unwrap().unwrap(),
+            TimedResult::Forfeited(_)
+        ));
+    }
+
+    #[test]
+    fn player_out_of_time_forfeits() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let game = SechsUndSechzig::with_seed(Variant::ThreePlayers, 7);
+        let control = TimeControl {
+            initial: Duration::from_millis(100),
+            increment: Duration::ZERO,
+        };
+        let server =
+            thread::spawn(move || serve_timed(&listener, game, control, TimeoutAction::Forfeit));
+
+        let mut client = Client::connect(&address, "SEAT");
+        let _others: Vec<_> = (0..2).map(|_| Client::connect(&address, "SEAT")).collect();
+        assert_eq!("SEATED 0", client.read_line());
+        let (_, current) = client.read_turn().unwrap();
+        // nobody moves, so player to move runs out of time
+        let lines: Vec<_> = (0..2).map(|_| client.read_line()).collect();
+        assert_eq!(
+            vec![
+                format!("TIMEOUT {}", current),
+                format!("FORFEITED {}", current)
+            ],
+            lines
+        );
+        assert_eq!(
+            TimedResult::Forfeited(current),
+            server.join().unwrap().unwrap()
+        );
+    }
+}
@@ -0,0 +1,630 @@
+//! Double-dummy analysis of play stage - finding results of perfect play with every hand known
+//!
+//! For every party, solver finds result it can secure against all other parties
+//! playing together against it. Result is positive number of points party
+//! scores when it wins the round, and negative number of points winning party
+//! scores otherwise.
+//!
+//! Search does not use `Round` itself, which would be too slow to copy at every
+//! node. Cards are kept in bit sets instead, and the rules of `Table` and
+//! `Round` - following suit, overtaking first card, declarations and immediate
+//! ends of contracts - are repeated here, checked against the game in tests.
+//! Two-player rounds can be solved once no more cards are drawn from the talon.
+
+use std::collections::HashMap;
+
+use tbsux::playered::Player;
+
+use crate::{
+    cards::{Card, Rank, Suit},
+    contract::{Contract, GameType, Party},
+    error::{SechsUndSechzigError, SusResult},
+    round::Round,
+    rules::Rules as HouseRules,
+    sus_move::SusMove,
+    talon::Talon,
+    variant::Variant,
+};
+
+/// Best result of every party playing the round, in order of `Contract::parties`
+///
+/// Fails with `WrongStage` when round is still in bidding, or cards are still
+/// drawn from the talon.
+pub fn solve(round: &Round) -> SusResult<Vec<(Party, i32)>> {
+    let (rules, position) = Rules::new(round)?;
+    Ok((0..rules.parties.len())
+        .map(|party| {
+            let mut solver = Solver::new(&rules, party);
+            let result = solver.search(position, i32::MIN, i32::MAX);
+            (rules.parties[party], result)
+        })
+        .collect())
+}
+
+/// Result of party of player to move after each of their legal moves, with perfect play afterwards
+pub fn evaluate_moves(round: &Round) -> SusResult<Vec<(SusMove, i32)>> {
+    let (rules, position) = Rules::new(round)?;
+    let player = rules.current_player(&position);
+    let mut solver = Solver::new(&rules, rules.party_of[player as usize]);
+    Ok(cards(rules.legal_cards(&position))
+        .map(|card| {
+            let result = match rules.play(position, card) {
+                Step::Finished(party, points) => solver.result(party, points),
+                Step::Continue(next) => solver.search(next, i32::MIN, i32::MAX),
+            };
+            (SusMove::PlayMove(card_at(card)), result)
+        })
+        .collect())
+}
+
+/// Set of cards, card with index `suit * 6 + rank` is in set when its bit is set
+type Cards = u32;
+
+const SUIT: Cards = 0b11_1111;
+
+fn index(card: &Card) -> u32 {
+    card.suit as u32 * 6 + card.rank as u32
+}
+
+fn card_at(index: u32) -> Card {
+    Card {
+        suit: Suit::SUITS[index as usize / 6],
+        rank: Rank::RANKS[index as usize % 6],
+    }
+}
+
+fn cards(set: Cards) -> impl Iterator<Item = u32> {
+    (0..24).filter(move |index| set & 1 << index != 0)
+}
+
+/// Everything about round which does not change during play
+struct Rules {
+    variant: Variant,
+    contract: Contract,
+    parties: Vec<Party>,
+    /// Index of party of every player in `parties`
+    party_of: Vec<usize>,
+    table_size: usize,
+    sitting_out: Option<Player>,
+    triumph: Option<u32>,
+    house: HouseRules,
+    /// Two players declare by leading the queen, others by ending trick with it
+    leader_declares: bool,
+    /// Index of party which closed the talon
+    closing_party: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    hands: [Cards; 4],
+    table: [(Player, u32); 4],
+    on_table: usize,
+    /// Player who starts current trick
+    leader: Player,
+    /// Points of every party, including declarations
+    points: [i32; 3],
+}
+
+enum Step {
+    Continue(Position),
+    /// Round ended, won by party with given index, with base points of the contract
+    Finished(usize, i32),
+}
+
+impl Rules {
+    fn new(round: &Round) -> SusResult<(Rules, Position)> {
+        let (table, stashes) = match (round.table(), round.stashes()) {
+            (Some(table), Some(stashes)) => (table, stashes),
+            _ => return Err(SechsUndSechzigError::WrongStage),
+        };
+        if round.talon().is_some_and(Talon::is_open) {
+            return Err(SechsUndSechzigError::WrongStage);
+        }
+        let variant = round.variant();
+        let contract = round.contract().clone();
+        let parties: Vec<Party> = contract.parties(&variant).copied().collect();
+        let party_of: Vec<usize> = (0..variant.number_of_players())
+            .map(|player| {
+                let party = contract.players_party(variant, player);
+                parties
+                    .iter()
+                    .position(|p| *p == party)
+                    .expect("Player has a party")
+            })
+            .collect();
+        let closing_party = round
+            .talon()
+            .and_then(Talon::closed_by)
+            .map(|closer| party_of[closer as usize]);
+        let sitting_out = if contract.dealers_teammate_plays() {
+            None
+        } else {
+            contract.dealers_teammate(&variant)
+        };
+        let rules = Rules {
+            variant,
+            table_size: contract.playing_players(&variant).count(),
+            sitting_out,
+            triumph: contract.game_type.triumph().map(|suit| suit as u32),
+            contract,
+            parties,
+            party_of,
+            house: *round.rules(),
+            leader_declares: round.talon().is_some(),
+            closing_party,
+        };
+
+        let mut position = Position {
+            hands: [0; 4],
+            table: [(0, 0); 4],
+            on_table: table.deals().len(),
+            leader: table
+                .deals()
+                .first()
+                .map(|(player, _)| *player)
+                .or_else(|| table.current_player())
+                .unwrap_or(0),
+            points: [0; 3],
+        };
+        for player in 0..variant.number_of_players() {
+            if let Ok(hand) = round.hands().hand(&player) {
+                position.hands[player as usize] =
+                    hand.full().fold(0, |set, card| set | 1 << index(card));
+            }
+        }
+        for (place, (player, card)) in table.deals().iter().enumerate() {
+            position.table[place] = (*player, index(card));
+        }
+        let points = stashes.points(rules.contract.game_type.triumph(), &rules.house);
+        for (party, party_points) in rules.parties.iter().zip(position.points.iter_mut()) {
+            *party_points = points.get(party).copied().unwrap_or(0);
+        }
+        Ok((rules, position))
+    }
+
+    fn next_player(&self, player: Player) -> Player {
+        let next = self.variant.next_player(player);
+        if Some(next) == self.sitting_out {
+            self.variant.next_player(next)
+        } else {
+            next
+        }
+    }
+
+    /// Same as `Table::current_player`
+    fn current_player(&self, position: &Position) -> Player {
+        match position.on_table {
+            0 if Some(position.leader) == self.sitting_out => self.next_player(position.leader),
+            0 => position.leader,
+            on_table => self.next_player(position.table[on_table - 1].0),
+        }
+    }
+
+    /// Same as `Table::filter_hand`
+    fn legal_cards(&self, position: &Position) -> Cards {
+        let hand = position.hands[self.current_player(position) as usize];
+        if position.on_table == 0 {
+            return hand;
+        }
+        let first = position.table[0].1;
+        let first_suit = SUIT << (first / 6 * 6);
+        let following = match hand & first_suit {
+            0 => hand,
+            same_suit => same_suit,
+        };
+        let mut overtaking = following & first_suit & !((2 << first) - 1);
+        if let Some(triumph) = self.triumph {
+            if first / 6 != triumph {
+                overtaking |= following & SUIT << (triumph * 6);
+            }
+        }
+        match overtaking {
+            0 => following,
+            overtaking => overtaking,
+        }
+    }
+
+    /// Same as `Round::handle_move` in play stage
+    fn play(&self, mut position: Position, card: u32) -> Step {
+        let player = self.current_player(&position);
+        let hand = &mut position.hands[player as usize];
+        let is_declaration = self.contract.can_declare()
+            && card % 6 == Rank::Queen as u32
+            && *hand & 1 << (card + 1) != 0;
+        *hand &= !(1 << card);
+        position.table[position.on_table] = (player, card);
+        position.on_table += 1;
+        if position.on_table < self.table_size {
+            return Step::Continue(position);
+        }
+
+        let trick = &position.table[..position.on_table];
+        let greatest_in = |suit: u32| {
+            trick
+                .iter()
+                .filter(|(_, card)| card / 6 == suit)
+                .max_by_key(|(_, card)| card % 6)
+        };
+        let (drawer, _) = *self
+            .triumph
+            .and_then(greatest_in)
+            .or_else(|| greatest_in(trick[0].1 / 6))
+            .expect("First card is in its own suit");
+        let drawing_party = self.party_of[drawer as usize];
+        let declaration = if self.leader_declares {
+            let (leader, led) = trick[0];
+            let declares = self.contract.can_declare()
+                && led % 6 == Rank::Queen as u32
+                && position.hands[leader as usize] & 1 << (led + 1) != 0;
+            Some((self.party_of[leader as usize], led)).filter(|_| declares)
+        } else {
+            Some((drawing_party, card)).filter(|_| is_declaration)
+        };
+        if let Some((declaring_party, queen)) = declaration {
+            let suit = card_at(queen).suit;
+            position.points[declaring_party] += self
+                .house
+                .declaration_points(suit, self.contract.game_type.triumph());
+        }
+        position.points[drawing_party] += trick
+            .iter()
+            .map(|(_, card)| card_at(*card).points())
+            .sum::<i32>();
+        position.on_table = 0;
+        position.leader = drawer;
+
+        if let Some(winner) = self.immediate_winner(&position, drawer) {
+            let (party, points) = self.after_closing(winner, true);
+            return Step::Finished(party, points);
+        }
+        let mut playing =
+            (0..self.variant.number_of_players()).filter(|p| Some(*p) != self.sitting_out);
+        if playing.all(|player| position.hands[player as usize] == 0) {
+            let winner = self.winner(&position, drawer);
+            let (party, points) = self.after_closing(winner, false);
+            return Step::Finished(party, points);
+        }
+        Step::Continue(position)
+    }
+
+    /// Same as `Round::immediate_winner`, parties with two of them are dealers and non-dealers
+    fn immediate_winner(&self, position: &Position, drawer: Player) -> Option<(usize, i32)> {
+        use GameType::*;
+
+        let points = &position.points;
+        let (dealers, non_dealers) = (0, 1);
+        let house = &self.house;
+        let table = &house.points;
+        match self.contract.game_type {
+            NonTriumph => None,
+            AskingAbout(_) => {
+                let drawers = self.party_of[drawer as usize];
+                [drawers, 1 - drawers]
+                    .iter()
+                    .copied()
+                    .find(|party| points[*party] >= house.threshold)
+                    .map(|party| (party, house.asking_about_points(points[1 - party])))
+            }
+            LookingFor(_) => {
+                if drawer != self.contract.dealer {
+                    Some((non_dealers, table.looking_for))
+                } else if points[dealers] > house.threshold {
+                    Some((dealers, table.looking_for))
+                } else {
+                    None
+                }
+            }
+            Misery => Some((non_dealers, table.misery)).filter(|_| points[dealers] > 0),
+            Shower => Some((non_dealers, table.shower)).filter(|_| points[non_dealers] > 0),
+        }
+    }
+
+    /// Same as `Round::after_closing`, closing party is dealers or non-dealers
+    fn after_closing(&self, (party, points): (usize, i32), early_win: bool) -> (usize, i32) {
+        match self.closing_party {
+            Some(closing) if party != closing || !early_win => {
+                (1 - closing, points.max(self.house.points.asking_about[1]))
+            }
+            _ => (party, points),
+        }
+    }
+
+    /// Same as `Round::winner`
+    fn winner(&self, position: &Position, drawer: Player) -> (usize, i32) {
+        use GameType::*;
+
+        let dealers = 0;
+        let table = &self.house.points;
+        match self.contract.game_type {
+            NonTriumph => {
+                let party = (0..self.parties.len())
+                    .min_by_key(|party| (position.points[*party], self.parties[*party]))
+                    .expect("There are parties");
+                (party, table.non_triumph)
+            }
+            AskingAbout(_) => (
+                self.party_of[drawer as usize],
+                table.asking_about_last_trick,
+            ),
+            LookingFor(_) => (dealers, table.looking_for),
+            Misery => (dealers, table.misery),
+            Shower => (dealers, table.shower),
+        }
+    }
+}
+
+/// Bounds of result of a position, found by earlier searches
+#[derive(Clone, Copy)]
+struct Bounds {
+    lower: i32,
+    upper: i32,
+}
+
+struct Solver<'a> {
+    rules: &'a Rules,
+    party: usize,
+    /// Positions between tricks, identified by cards left, leader and points of parties
+    known: HashMap<(Cards, Player, [i32; 3]), Bounds>,
+}
+
+impl<'a> Solver<'a> {
+    fn new(rules: &'a Rules, party: usize) -> Solver<'a> {
+        Solver {
+            rules,
+            party,
+            known: HashMap::new(),
+        }
+    }
+
+    fn result(&self, winner: usize, base_points: i32) -> i32 {
+        let points = self.rules.contract.points(base_points);
+        if winner == self.party {
+            points
+        } else {
+            -points
+        }
+    }
+
+    /// Alpha-beta search, with positions between tricks remembered
+    fn search(&mut self, position: Position, mut alpha: i32, mut beta: i32) -> i32 {
+        let key = match position.on_table {
+            0 => Some((
+                position.hands.iter().fold(0, |set, hand| set | hand),
+                position.leader,
+                position.points,
+            )),
+            _ => None,
+        };
+        if let Some(bounds) = key.as_ref().and_then(|key| self.known.get(key)) {
+            if bounds.lower >= beta || bounds.lower == bounds.upper {
+                return bounds.lower;
+            }
+            if bounds.upper <= alpha {
+                return bounds.upper;
+            }
+            alpha = alpha.max(bounds.lower);
+            beta = beta.min(bounds.upper);
+        }
+
+        let (alpha_searched, beta_searched) = (alpha, beta);
+        let player = self.rules.current_player(&position);
+        let maximizing = self.rules.party_of[player as usize] == self.party;
+        let mut best = if maximizing { i32::MIN } else { i32::MAX };
+        for card in cards(self.rules.legal_cards(&position)) {
+            let result = match self.rules.play(position, card) {
+                Step::Finished(party, points) => self.result(party, points),
+                Step::Continue(next) => self.search(next, alpha, beta),
+            };
+            if maximizing {
+                best = best.max(result);
+                alpha = alpha.max(result);
+            } else {
+                best = best.min(result);
+                beta = beta.min(result);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        if let Some(key) = key {
+            let bounds = self.known.entry(key).or_insert(Bounds {
+                lower: i32::MIN,
+                upper: i32::MAX,
+            });
+            // result outside of searched window is only a bound of real one
+            if best > alpha_searched {
+                bounds.lower = bounds.lower.max(best);
+            }
+            if best < beta_searched {
+                bounds.upper = bounds.upper.min(best);
+            }
+        }
+        best
+    }
+}
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use rand::prelude::*;
+    use tbsux::prelude::*;
+
+    use super::*;
+    use crate::{
+        bidding::Bid,
+        game::{SechsUndSechzig, SechsUndSechzigState},
+        round::RoundResult,
+    };
+
+    /// Round of game with given seed, right after bidding ended with `contract`
+    fn round_after(variant: Variant, seed: u64, contract: &[Bid]) -> Round {
+        let mut state: SechsUndSechzigState =
+            SechsUndSechzig::with_seed(variant, seed).initial_state();
+        let mut bids = contract.iter();
+        while state.round().table().is_none() {
+            let bid = match bids.next() {
+                Some(bid) => *bid,
+                None => Bid::Pass,
+            };
+            state = state.move_reducer(SusMove::BiddingMove(bid)).unwrap();
+        }
+        state.round().clone()
+    }
+
+    fn play(round: &Round, moves: usize) -> Round {
+        let mut round = round.clone();
+        for _ in 0..moves {
+            let mv = round.legal_moves()[0];
+            round.handle_move(mv).unwrap();
+        }
+        round
+    }
+
+    /// Plain minimax, without pruning nor remembered positions
+    fn minimax(round: &Round, party: Party) -> i32 {
+        let variant = round.variant();
+        let maximizing = round
+            .contract()
+            .players_party(variant, round.current_player())
+            == party;
+        let values = round.legal_moves().into_iter().map(|mv| {
+            let mut next = round.clone();
+            match next.handle_move(mv).unwrap() {
+                RoundResult::Finished(result) => {
+                    if result.winning_party == party {
+                        result.points()
+                    } else {
+                        -result.points()
+                    }
+                }
+                RoundResult::Contiune => minimax(&next, party),
+            }
+        });
+        if maximizing {
+            values.max().unwrap()
+        } else {
+            values.min().unwrap()
+        }
+    }
+
+    #[test]
+    fn rules_are_the_same_as_in_game() {
+        for seed in 0..9 {
+            let variant = match seed % 3 {
+                0 => Variant::TwoPlayers,
+                1 => Variant::ThreePlayers,
+                _ => Variant::FourPlayers,
+            };
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut state = SechsUndSechzig::with_seed(variant, seed).initial_state();
+            while let ProgressReport::InProgress(_) = state.progress_report() {
+                let round = state.round();
+                let mv = *state.legal_moves().choose(&mut rng).unwrap();
+                let (rules, position) = match Rules::new(round) {
+                    Ok(solved) => solved,
+                    Err(_) => {
+                        state = state.move_reducer(mv).unwrap();
+                        continue;
+                    }
+                };
+                assert_eq!(round.current_player(), rules.current_player(&position));
+                let legal: Vec<_> = cards(rules.legal_cards(&position))
+                    .map(|card| SusMove::PlayMove(card_at(card)))
+                    .collect();
+                let mut expected = round.legal_moves();
+                expected.sort_by_key(|mv| match mv {
+                    SusMove::PlayMove(card) => index(card),
+                    SusMove::BiddingMove(_) | SusMove::TalonMove(_) => 24,
+                });
+                assert_eq!(expected, legal);
+
+                let card = match mv {
+                    SusMove::PlayMove(card) => index(&card),
+                    SusMove::BiddingMove(_) | SusMove::TalonMove(_) => {
+                        unreachable!("Round is in play stage, without talon moves")
+                    }
+                };
+                let mut next = round.clone();
+                match (next.handle_move(mv).unwrap(), rules.play(position, card)) {
+                    (RoundResult::Finished(result), Step::Finished(party, solved)) => {
+                        let winner = rules.party_of[result.winners[0] as usize];
+                        assert_eq!((winner, result.base_points), (party, solved));
+                    }
+                    (RoundResult::Contiune, Step::Continue(_)) => (),
+                    _ => panic!("Only one of round and solver finished after {}", mv),
+                }
+                state = state.move_reducer(mv).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn agrees_with_minimax_near_end_of_round() {
+        let contracts: [&[Bid]; 3] = [
+            &[],
+            &[Bid::Game(GameType::Misery)],
+            &[Bid::Game(GameType::Shower)],
+        ];
+        for (seed, contract) in contracts.iter().enumerate() {
+            let round = play(
+                &round_after(Variant::ThreePlayers, seed as u64, contract),
+                13,
+            );
+            if round.table().is_none() {
+                continue;
+            }
+            for (party, result) in solve(&round).unwrap() {
+                assert_eq!(minimax(&round, party), result, "{:?}", party);
+            }
+        }
+    }
+
+    #[test]
+    fn results_of_two_parties_are_opposite() {
+        let round = round_after(Variant::FourPlayers, 5, &[Bid::Game(GameType::Misery)]);
+        let results = solve(&round).unwrap();
+        assert_eq!(2, results.len());
+        assert_eq!(results[0].1, -results[1].1);
+    }
+
+    #[test]
+    fn solves_full_three_players_deal_quickly() {
+        let round = round_after(Variant::ThreePlayers, 3, &[]);
+        assert_eq!(8, round.hands().hand(&0).unwrap().len());
+        let started = Instant::now();
+        assert_eq!(3, solve(&round).unwrap().len());
+        // generous limit, as tests may run without optimizations
+        assert!(started.elapsed().as_secs() < 5);
+    }
+
+    #[test]
+    fn best_move_reaches_solved_result() {
+        let round = play(&round_after(Variant::FourPlayers, 8, &[]), 6);
+        let party = round
+            .contract()
+            .players_party(round.variant(), round.current_player());
+        let best = evaluate_moves(&round)
+            .unwrap()
+            .into_iter()
+            .map(|(_, result)| result)
+            .max();
+        let solved = solve(&round)
+            .unwrap()
+            .into_iter()
+            .find(|(p, _)| *p == party);
+        assert_eq!(solved.map(|(_, result)| result), best);
+    }
+
+    #[test]
+    fn cannot_solve_bidding() {
+        let round = SechsUndSechzig::with_seed(Variant::ThreePlayers, 1)
+            .initial_state()
+            .round()
+            .clone();
+        assert!(matches!(
+            solve(&round),
+            Err(SechsUndSechzigError::WrongStage)
+        ));
+    }
+}
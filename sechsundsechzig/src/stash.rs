@@ -4,18 +4,17 @@ use crate::{
     cards::{Card, Suit},
     contract::Party,
     error::{SechsUndSechzigError, SusResult},
+    rules::Rules,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Stash {
     cards: Vec<Card>,
     declarations: Vec<Suit>,
 }
 
 impl Stash {
-    const DECLARATION_POINTS: i32 = 20;
-    const TRIUMPH_DECLARATION_POINTS: i32 = 40;
-
     pub fn empty() -> Stash {
         Stash {
             cards: vec![],
@@ -23,22 +22,29 @@ impl Stash {
         }
     }
 
-    pub fn points(&self, triumph: Option<Suit>) -> i32 {
-        let cards_points: i32 = self.cards.iter().map(Card::points).sum();
+    pub fn points(&self, triumph: Option<Suit>, rules: &Rules) -> i32 {
+        let cards_points = self.card_points();
         let declaration_points: i32 = self
             .declarations
             .iter()
-            .map(|suit| {
-                if Some(*suit) == triumph {
-                    Stash::TRIUMPH_DECLARATION_POINTS
-                } else {
-                    Stash::DECLARATION_POINTS
-                }
-            })
+            .map(|suit| rules.declaration_points(*suit, triumph))
             .sum();
         declaration_points + cards_points
     }
 
+    /// Points of taken cards, without declarations
+    pub fn card_points(&self) -> i32 {
+        self.cards.iter().map(Card::points).sum()
+    }
+
+    pub fn cards(&self) -> impl Iterator<Item = &Card> {
+        self.cards.iter()
+    }
+
+    pub fn declarations(&self) -> impl Iterator<Item = &Suit> {
+        self.declarations.iter()
+    }
+
     pub fn declare(&mut self, suit: Suit) {
         self.declarations.push(suit);
     }
@@ -51,7 +57,10 @@ impl Stash {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Stashes(HashMap<Party, Stash>);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stashes(
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_pairs"))] HashMap<Party, Stash>,
+);
 
 impl Stashes {
     pub fn empty<'a>(parties: impl Iterator<Item = &'a Party>) -> Stashes {
@@ -74,10 +83,20 @@ impl Stashes {
         }
     }
 
-    pub fn points(&self, triumph: Option<Suit>) -> HashMap<Party, i32> {
+    /// Cards taken by all parties
+    pub fn cards(&self) -> impl Iterator<Item = &Card> {
+        self.0.values().flat_map(Stash::cards)
+    }
+
+    /// Suits declared by all parties
+    pub fn declarations(&self) -> impl Iterator<Item = &Suit> {
+        self.0.values().flat_map(Stash::declarations)
+    }
+
+    pub fn points(&self, triumph: Option<Suit>, rules: &Rules) -> HashMap<Party, i32> {
         self.0
             .iter()
-            .map(|(k, v)| (*k, v.points(triumph)))
+            .map(|(k, v)| (*k, v.points(triumph, rules)))
             .collect()
     }
 }
@@ -0,0 +1,251 @@
+//! Statistics of many finished games, showing how often each contract is played and won
+//!
+//! Contract succeeds when team of its dealer - the player who bid it - scores
+//! points for the round.
+
+use std::{
+    fmt,
+    io::{self, Write},
+};
+
+use tbsux::{playered::Player, prelude::*};
+
+use crate::{
+    contract::{Contract, GameType},
+    game::SechsUndSechzigState,
+    team::Team,
+    variant::Variant,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ContractStats {
+    pub rounds: u32,
+    pub succeeded: u32,
+    pub multipliers: i64,
+}
+
+impl ContractStats {
+    pub fn success_rate(&self) -> f64 {
+        ratio(self.succeeded as f64, self.rounds)
+    }
+
+    pub fn average_multiplier(&self) -> f64 {
+        ratio(self.multipliers as f64, self.rounds)
+    }
+
+    fn add(&mut self, contract: &Contract, succeeded: bool) {
+        self.rounds += 1;
+        self.succeeded += succeeded as u32;
+        self.multipliers += contract.multiplier as i64;
+    }
+}
+
+/// Games started by dealer of the first round, and how many of them dealer's team won
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DealerStats {
+    pub games: u32,
+    pub won: u32,
+}
+
+impl DealerStats {
+    pub fn win_rate(&self) -> f64 {
+        ratio(self.won as f64, self.games)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statistics {
+    pub games: u32,
+    /// Statistics of every game type, indexed by `GameType::index`
+    pub contracts: [ContractStats; GameType::NAMES.len()],
+    /// Statistics of games by their starting dealer
+    pub dealers: Vec<DealerStats>,
+}
+
+impl Statistics {
+    pub fn new(variant: Variant) -> Statistics {
+        Statistics {
+            games: 0,
+            contracts: Default::default(),
+            dealers: vec![DealerStats::default(); variant.number_of_players() as usize],
+        }
+    }
+
+    /// Adds every finished round of a game
+    pub fn record(&mut self, finished: &SechsUndSechzigState) {
+        let variant = finished.variant();
+        for result in finished.results() {
+            let contract = &result.contract;
+            let dealers_team = team_of(variant, contract.dealer);
+            let succeeded = result
+                .winners
+                .iter()
+                .any(|winner| team_of(variant, *winner) == dealers_team);
+            self.contracts[contract.game_type.index()].add(contract, succeeded);
+        }
+
+        // dealer of contract is the player who bid it, so first dealer comes from the deal
+        let starting_dealer = finished.game().initial_state().round().contract().dealer;
+        self.games += 1;
+        let dealer = &mut self.dealers[starting_dealer as usize];
+        dealer.games += 1;
+        let starting_team = team_of(variant, starting_dealer);
+        dealer.won += (finished.score().winner() == Some(&starting_team)) as u32;
+    }
+
+    pub fn rounds(&self) -> u32 {
+        self.contracts.iter().map(|stats| stats.rounds).sum()
+    }
+
+    pub fn rounds_per_game(&self) -> f64 {
+        ratio(self.rounds() as f64, self.games)
+    }
+
+    pub fn average_multiplier(&self) -> f64 {
+        let multipliers: i64 = self.contracts.iter().map(|stats| stats.multipliers).sum();
+        ratio(multipliers as f64, self.rounds())
+    }
+
+    /// Writes statistics as CSV with `metric,group,value` columns
+    pub fn write_csv(&self, writer: &mut impl Write) -> io::Result<()> {
+        writeln!(writer, "metric,group,value")?;
+        writeln!(writer, "games,all,{}", self.games)?;
+        writeln!(writer, "rounds_per_game,all,{:.4}", self.rounds_per_game())?;
+        writeln!(
+            writer,
+            "average_multiplier,all,{:.4}",
+            self.average_multiplier()
+        )?;
+        for (name, stats) in GameType::NAMES.iter().zip(&self.contracts) {
+            writeln!(writer, "rounds,{},{}", name, stats.rounds)?;
+            let share = ratio(stats.rounds as f64, self.rounds());
+            writeln!(writer, "share,{},{:.4}", name, share)?;
+            writeln!(writer, "success_rate,{},{:.4}", name, stats.success_rate())?;
+            writeln!(
+                writer,
+                "average_multiplier,{},{:.4}",
+                name,
+                stats.average_multiplier()
+            )?;
+        }
+        for (dealer, stats) in self.dealers.iter().enumerate() {
+            writeln!(writer, "dealer_games,player {},{}", dealer, stats.games)?;
+            writeln!(
+                writer,
+                "dealer_win_rate,player {},{:.4}",
+                dealer,
+                stats.win_rate()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "games: {}, rounds per game: {:.2}, average multiplier: {:.2}\n",
+            self.games,
+            self.rounds_per_game(),
+            self.average_multiplier()
+        )?;
+        writeln!(
+            f,
+            "{:<14} {:>8} {:>7} {:>8} {:>11}",
+            "contract", "rounds", "share", "success", "multiplier"
+        )?;
+        for (name, stats) in GameType::NAMES.iter().zip(&self.contracts) {
+            writeln!(
+                f,
+                "{:<14} {:>8} {:>6.1}% {:>7.1}% {:>11.2}",
+                name,
+                stats.rounds,
+                100.0 * ratio(stats.rounds as f64, self.rounds()),
+                100.0 * stats.success_rate(),
+                stats.average_multiplier()
+            )?;
+        }
+        writeln!(
+            f,
+            "\n{:<16} {:>6} {:>9}",
+            "starting dealer", "games", "win rate"
+        )?;
+        for (dealer, stats) in self.dealers.iter().enumerate() {
+            writeln!(
+                f,
+                "player {:<9} {:>6} {:>8.1}%",
+                dealer,
+                stats.games,
+                100.0 * stats.win_rate()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn team_of(variant: Variant, player: Player) -> Team {
+    Team::teams(variant)
+        .find(|team| {
+            team.players()
+                .is_ok_and(|mut players| players.any(|p| p == player))
+        })
+        .expect("Every player has a team")
+}
+
+fn ratio(value: f64, count: u32) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        value / count as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::SechsUndSechzig;
+
+    fn finished_game(variant: Variant, seed: u64) -> SechsUndSechzigState {
+        let mut state = SechsUndSechzig::with_seed(variant, seed).initial_state();
+        while let ProgressReport::InProgress(_) = state.progress_report() {
+            state = state.move_reducer(state.legal_moves()[0]).unwrap();
+        }
+        state
+    }
+
+    #[test]
+    fn counts_every_round_and_game() {
+        let mut stats = Statistics::new(Variant::ThreePlayers);
+        let mut rounds = 0;
+        for seed in 0..3 {
+            let finished = finished_game(Variant::ThreePlayers, seed);
+            rounds += finished.results().len() as u32;
+            stats.record(&finished);
+        }
+        assert_eq!(3, stats.games);
+        assert_eq!(rounds, stats.rounds());
+        assert_eq!(
+            3,
+            stats.dealers.iter().map(|dealer| dealer.games).sum::<u32>()
+        );
+        assert!(stats.rounds() >= 3);
+        let succeeded: u32 = stats.contracts.iter().map(|c| c.succeeded).sum();
+        assert!(succeeded <= stats.rounds());
+        assert!(stats.average_multiplier() >= 1.0);
+    }
+
+    #[test]
+    fn csv_has_every_contract() {
+        let mut stats = Statistics::new(Variant::FourPlayers);
+        stats.record(&finished_game(Variant::FourPlayers, 1));
+        let mut csv = vec![];
+        stats.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("metric,group,value\ngames,all,1\n"));
+        for name in GameType::NAMES.iter() {
+            assert!(csv.contains(&format!("success_rate,{},", name)));
+        }
+        assert!(csv.lines().all(|line| line.split(',').count() == 3));
+    }
+}
@@ -1,10 +1,17 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
-use crate::{bidding::Bid, cards::Card, contract::GameType, error::SechsUndSechzigError};
+use crate::{
+    bidding::Bid, cards::Card, contract::GameType, error::SechsUndSechzigError, talon::TalonAction,
+};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum SusMove {
     BiddingMove(Bid),
     PlayMove(Card),
+    /// Exchanging triumph card or closing talon in two-player game
+    TalonMove(TalonAction),
 }
 
 impl FromStr for SusMove {
@@ -14,6 +21,7 @@ impl FromStr for SusMove {
         use Bid::*;
         use GameType::*;
         use SusMove::*;
+        use TalonAction::*;
 
         let split_str: Vec<_> = s.split(" ").collect();
         let other_str = if split_str.len() > 1 {
@@ -21,7 +29,7 @@ impl FromStr for SusMove {
         } else {
             None
         };
-        if split_str.len() > 0 {
+        if !split_str.is_empty() {
             match (&split_str[0].to_ascii_lowercase() as &str, other_str) {
                 ("pass", None) => Ok(BiddingMove(Pass)),
                 ("pas", None) => Ok(BiddingMove(Pass)),
@@ -48,6 +56,14 @@ impl FromStr for SusMove {
                 ("durh", None) => Ok(BiddingMove(Game(Shower))),
                 ("s", None) => Ok(BiddingMove(Game(Shower))),
 
+                ("exchange", None) => Ok(TalonMove(ExchangeTriumph)),
+                ("wymieniam", None) => Ok(TalonMove(ExchangeTriumph)),
+                ("x", None) => Ok(TalonMove(ExchangeTriumph)),
+
+                ("close", None) => Ok(TalonMove(Close)),
+                ("zamykam", None) => Ok(TalonMove(Close)),
+                ("z", None) => Ok(TalonMove(Close)),
+
                 _ => Ok(PlayMove(s.parse()?)),
             }
         } else {
@@ -55,3 +71,53 @@ impl FromStr for SusMove {
         }
     }
 }
+
+impl fmt::Display for SusMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Bid::*;
+        use GameType::*;
+        use SusMove::*;
+
+        match self {
+            BiddingMove(Pass) => write!(f, "pass"),
+            BiddingMove(Raise) => write!(f, "raise"),
+            BiddingMove(Game(NonTriumph)) => write!(f, "non-triumph"),
+            BiddingMove(Game(AskingAbout(suit))) => write!(f, "ask-about {}", suit),
+            BiddingMove(Game(LookingFor(suit))) => write!(f, "look-for {}", suit),
+            BiddingMove(Game(Misery)) => write!(f, "misery"),
+            BiddingMove(Game(Shower)) => write!(f, "shower"),
+            PlayMove(card) => write!(f, "{} {}", card.rank, card.suit),
+            TalonMove(action) => write!(f, "{}", action),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::{Rank, Suit};
+
+    #[test]
+    fn displayed_moves_are_parsed_back() {
+        let moves = Bid::all()
+            .map(SusMove::BiddingMove)
+            .chain(
+                Card::deck()
+                    .filter(|card| card.suit == Suit::Heart)
+                    .map(SusMove::PlayMove),
+            )
+            .chain(TalonAction::ACTIONS.iter().copied().map(SusMove::TalonMove));
+        for mv in moves {
+            assert_eq!(mv, mv.to_string().parse().unwrap());
+        }
+    }
+
+    #[test]
+    fn play_move_is_displayed_without_brackets() {
+        let mv = SusMove::PlayMove(Card {
+            rank: Rank::Ten,
+            suit: Suit::Spade,
+        });
+        assert_eq!("10 ♠", mv.to_string());
+    }
+}
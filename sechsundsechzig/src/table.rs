@@ -12,6 +12,7 @@ use crate::{
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Table {
     variant: Variant,
     contract: Contract,
@@ -67,7 +68,7 @@ impl Table {
     }
 
     pub fn drawer(&self) -> Option<Player> {
-        if let Some(_) = self.current_player() {
+        if self.current_player().is_some() {
             None
         } else {
             self.greatest_card().and_then(|greatest| {
@@ -80,15 +81,20 @@ impl Table {
         }
     }
 
+    /// Cards on table together with players who played them, in order of playing
+    pub fn deals(&self) -> &[(Player, Card)] {
+        &self.deals
+    }
+
     pub fn cards(&self) -> impl Iterator<Item = &Card> {
         self.deals.iter().map(|(_, card)| card)
     }
 
     pub fn filter_hand(&self, hand: &Hand) -> impl Iterator<Item = Card> {
         let satisfying_color_condition =
-            self.satisfying_condition(hand.full().map(|c| *c), |Card { suit, .. }| {
+            self.satisfying_condition(hand.full().copied(), |Card { suit, .. }| {
                 self.first_suit()
-                    .map_or(true, |first_suit| suit == first_suit)
+                    .is_none_or(|first_suit| suit == first_suit)
             });
         let satisfying_overbidding_condition =
             self.satisfying_condition(satisfying_color_condition, |card| {
@@ -116,13 +122,13 @@ impl Table {
         }
     }
 
-    fn satisfying_condition<'a>(
+    fn satisfying_condition(
         &self,
         cards: impl Iterator<Item = Card>,
         condition: impl Fn(&Card) -> bool,
     ) -> Box<dyn Iterator<Item = Card>> {
         let all_cards: Vec<_> = cards.collect();
-        let satisfying_cards: Vec<Card> = all_cards.iter().map(|c| *c).filter(condition).collect();
+        let satisfying_cards: Vec<Card> = all_cards.iter().copied().filter(condition).collect();
         match satisfying_cards {
             cards if cards.is_empty() => Box::new(all_cards.into_iter()),
             cards => Box::new(cards.into_iter()),
@@ -162,7 +168,7 @@ impl Table {
 
 impl fmt::Display for Table {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.deals.len() == 0 {
+        if self.deals.is_empty() {
             writeln!(f, "empty")
         } else {
             for (player, card) in &self.deals {
@@ -0,0 +1,302 @@
+//! Talon of two-player game - cards left after dealing, drawn after every trick
+//!
+//! Last card of the talon lies face up and shows the triumph. Player leading a
+//! trick may exchange the nine of triumph for it, or close the talon, after
+//! which no more cards are drawn and players have to follow suit.
+
+use std::fmt;
+
+use tbsux::playered::Player;
+
+use crate::{
+    cards::{Card, Rank, Suit},
+    error::{SechsUndSechzigError, SusResult},
+    hands::Hand,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TalonAction {
+    /// Take face-up card in exchange for the nine of triumph
+    ExchangeTriumph,
+    /// Stop drawing cards, player closing has to reach the threshold alone
+    Close,
+}
+
+impl TalonAction {
+    pub const ACTIONS: [TalonAction; 2] = [TalonAction::ExchangeTriumph, TalonAction::Close];
+}
+
+impl fmt::Display for TalonAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TalonAction::ExchangeTriumph => write!(f, "exchange"),
+            TalonAction::Close => write!(f, "close"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Talon {
+    /// Face-down cards, drawn from the front
+    stock: Vec<Card>,
+    /// Face-up card, drawn last, `None` once it was drawn
+    triumph_card: Option<Card>,
+    triumph: Suit,
+    closed_by: Option<Player>,
+}
+
+/// Talon as seen by players, who know only the number of face-down cards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PublicTalon {
+    stock_size: usize,
+    triumph_card: Option<Card>,
+    triumph: Suit,
+    closed_by: Option<Player>,
+}
+
+impl PublicTalon {
+    pub fn triumph(&self) -> Suit {
+        self.triumph
+    }
+
+    pub fn triumph_card(&self) -> Option<Card> {
+        self.triumph_card
+    }
+
+    pub fn len(&self) -> usize {
+        self.stock_size + self.triumph_card.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn closed_by(&self) -> Option<Player> {
+        self.closed_by
+    }
+
+    /// Whether cards are still drawn after tricks, so players do not have to follow suit
+    pub fn is_open(&self) -> bool {
+        self.closed_by.is_none() && !self.is_empty()
+    }
+
+    /// Talon with given face-down cards, as many as are left in the talon
+    pub(crate) fn with_stock(&self, stock: Vec<Card>) -> Talon {
+        debug_assert_eq!(self.stock_size, stock.len());
+        Talon {
+            stock,
+            triumph_card: self.triumph_card,
+            triumph: self.triumph,
+            closed_by: self.closed_by,
+        }
+    }
+}
+
+impl Talon {
+    /// Talon of cards left after dealing, last of them is turned face up
+    pub fn new(mut cards: Vec<Card>) -> Option<Talon> {
+        let triumph_card = cards.pop()?;
+        Some(Talon {
+            stock: cards,
+            triumph_card: Some(triumph_card),
+            triumph: triumph_card.suit,
+            closed_by: None,
+        })
+    }
+
+    pub fn triumph(&self) -> Suit {
+        self.triumph
+    }
+
+    pub fn triumph_card(&self) -> Option<Card> {
+        self.triumph_card
+    }
+
+    /// Cards left in talon, in order of drawing
+    pub fn cards(&self) -> Vec<Card> {
+        self.stock
+            .iter()
+            .copied()
+            .chain(self.triumph_card)
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.stock.len() + self.triumph_card.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn closed_by(&self) -> Option<Player> {
+        self.closed_by
+    }
+
+    /// Whether cards are still drawn after tricks, so players do not have to follow suit
+    pub fn is_open(&self) -> bool {
+        self.closed_by.is_none() && !self.is_empty()
+    }
+
+    /// Actions player leading a trick can take, holding `hand`
+    pub fn actions<'a>(&'a self, hand: &'a Hand) -> impl Iterator<Item = TalonAction> + 'a {
+        TalonAction::ACTIONS
+            .iter()
+            .copied()
+            .filter(move |action| self.can_take(*action, hand))
+    }
+
+    /// Takes action of player leading a trick, holding `hand`
+    pub fn take(&mut self, action: TalonAction, player: Player, hand: &mut Hand) -> SusResult<()> {
+        if !self.can_take(action, hand) {
+            return Err(SechsUndSechzigError::TalonMoveNotAllowed);
+        }
+        match action {
+            TalonAction::ExchangeTriumph => {
+                let nine = hand.deal(self.triumph_nine())?;
+                if let Some(card) = self.triumph_card.replace(nine) {
+                    hand.take(card);
+                }
+            }
+            TalonAction::Close => self.closed_by = Some(player),
+        }
+        Ok(())
+    }
+
+    /// Draws next card, face-up card is drawn last
+    pub fn draw(&mut self) -> Option<Card> {
+        if self.stock.is_empty() {
+            self.triumph_card.take()
+        } else {
+            Some(self.stock.remove(0))
+        }
+    }
+
+    /// Talon as seen by players, without face-down cards
+    pub fn public(&self) -> PublicTalon {
+        PublicTalon {
+            stock_size: self.stock.len(),
+            triumph_card: self.triumph_card,
+            triumph: self.triumph,
+            closed_by: self.closed_by,
+        }
+    }
+
+    /// Exchange and closing are allowed until only the last two cards are left to draw
+    fn can_take(&self, action: TalonAction, hand: &Hand) -> bool {
+        let can_change = self.is_open() && self.len() > 2;
+        match action {
+            TalonAction::ExchangeTriumph => {
+                can_change && hand.full().any(|card| *card == self.triumph_nine())
+            }
+            TalonAction::Close => can_change,
+        }
+    }
+
+    fn triumph_nine(&self) -> Card {
+        Card {
+            rank: Rank::Nine,
+            suit: self.triumph,
+        }
+    }
+}
+
+impl fmt::Display for Talon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.public().fmt(f)
+    }
+}
+
+impl fmt::Display for PublicTalon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.triumph_card {
+            Some(card) => write!(f, "{} cards, {} face up", self.len(), card)?,
+            None => write!(f, "empty, triumph: {}", self.triumph)?,
+        }
+        if let Some(player) = self.closed_by {
+            write!(f, ", closed by player {}", player)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::cards;
+    use crate::hands::Hands;
+
+    fn talon() -> Talon {
+        Talon::new(cards(
+            "9 s, J s, Q s, K s, 10 s, A s, J c, Q c, K c, 10 c, A c, J h",
+        ))
+        .unwrap()
+    }
+
+    fn hand(held: &str) -> Hand {
+        let hands = Hands::from_cards(vec![(0, cards(held))].into_iter().collect());
+        hands.hand(&0).unwrap().clone()
+    }
+
+    #[test]
+    fn face_up_card_is_drawn_last() {
+        let mut talon = talon();
+        assert_eq!(Suit::Heart, talon.triumph());
+        let drawn: Vec<_> = std::iter::from_fn(|| talon.draw()).collect();
+        assert_eq!(
+            cards("9 s, J s, Q s, K s, 10 s, A s, J c, Q c, K c, 10 c, A c, J h"),
+            drawn
+        );
+        assert!(!talon.is_open());
+        assert_eq!(Suit::Heart, talon.triumph());
+    }
+
+    #[test]
+    fn nine_of_triumph_is_exchanged_for_face_up_card() {
+        let mut talon = talon();
+        let mut hand = hand("9 h, A d");
+        talon
+            .take(TalonAction::ExchangeTriumph, 0, &mut hand)
+            .unwrap();
+        assert_eq!(cards("9 h")[0], talon.triumph_card().unwrap());
+        assert_eq!(cards("A d, J h"), hand.full().copied().collect::<Vec<_>>());
+        assert_eq!(
+            Err(SechsUndSechzigError::TalonMoveNotAllowed),
+            talon.take(TalonAction::ExchangeTriumph, 0, &mut hand)
+        );
+    }
+
+    #[test]
+    fn closed_talon_cannot_be_changed() {
+        let mut talon = talon();
+        let mut hand = hand("9 h, A d");
+        talon.take(TalonAction::Close, 1, &mut hand).unwrap();
+        assert_eq!(Some(1), talon.closed_by());
+        assert!(!talon.is_open());
+        assert_eq!(0, talon.actions(&hand).count());
+    }
+
+    #[test]
+    fn last_two_cards_cannot_be_changed() {
+        let mut talon = talon();
+        let hand = hand("9 h, A d");
+        while talon.len() > 2 {
+            assert_eq!(2, talon.actions(&hand).count());
+            talon.draw();
+        }
+        assert_eq!(0, talon.actions(&hand).count());
+    }
+
+    #[test]
+    fn public_view_hides_stock() {
+        let talon = talon();
+        let public = talon.public();
+        assert_eq!(talon.len(), public.len());
+        assert_eq!(talon.triumph_card(), public.triumph_card());
+        assert_eq!(talon, public.with_stock(talon.stock.clone()));
+    }
+}
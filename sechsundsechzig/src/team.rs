@@ -8,12 +8,14 @@ use crate::{
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Team(pub u32, pub Variant);
 
 impl Team {
     pub fn teams(variant: Variant) -> impl Iterator<Item = Team> {
         use Variant::*;
         let teams = match variant {
+            TwoPlayers => 2,
             ThreePlayers => 3,
             FourPlayers => 2,
         };
@@ -23,6 +25,7 @@ impl Team {
     pub fn players(&self) -> SusResult<impl Iterator<Item = Player>> {
         use Variant::*;
         match self {
+            Team(id @ 0..=1, TwoPlayers) => Ok(vec![*id].into_iter()),
             Team(id @ 0..=2, ThreePlayers) => Ok(vec![*id].into_iter()),
             Team(0, FourPlayers) => Ok(vec![0, 2].into_iter()),
             Team(1, FourPlayers) => Ok(vec![1, 3].into_iter()),
@@ -33,7 +36,7 @@ impl Team {
     pub fn for_players(players: Vec<Player>, variant: Variant) -> impl Iterator<Item = Team> {
         let mut teams: Vec<Team> = vec![];
         match variant {
-            Variant::ThreePlayers => players
+            Variant::TwoPlayers | Variant::ThreePlayers => players
                 .iter()
                 .for_each(|player| teams.push(Team(*player, variant))),
             Variant::FourPlayers if players.contains(&0) && players.contains(&2) => {
@@ -1,8 +1,16 @@
+use std::str::FromStr;
+
 use tbsux::playered::Player;
 
-/// Describes variant of game (three players/four players)
+use crate::error::SechsUndSechzigError;
+
+/// Describes variant of game (two players/three players/four players)
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Variant {
+    /// Classic Sixty-six, with six cards dealt and the rest drawn from the talon
+    TwoPlayers,
     ThreePlayers,
     FourPlayers,
 }
@@ -12,13 +20,28 @@ impl Variant {
         use Variant::*;
 
         match self {
+            TwoPlayers => 2,
             ThreePlayers => 3,
             FourPlayers => 4,
         }
     }
 
     pub fn cards_per_player(&self) -> usize {
-        24 / self.number_of_players() as usize
+        use Variant::*;
+
+        match self {
+            TwoPlayers => 6,
+            _ => 24 / self.number_of_players() as usize,
+        }
+    }
+
+    /// Cards left after dealing, face-down stock together with face-up triumph card
+    pub fn talon_size(&self) -> usize {
+        24 - self.cards_per_player() * self.number_of_players() as usize
+    }
+
+    pub fn has_talon(&self) -> bool {
+        self.talon_size() > 0
     }
 
     pub fn next_player(&self, player: Player) -> Player {
@@ -26,19 +49,52 @@ impl Variant {
     }
 }
 
+impl FromStr for Variant {
+    type Err = SechsUndSechzigError;
+
+    /// Variant named by number of players - `two`, `three` or `four`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "two" => Ok(Variant::TwoPlayers),
+            "three" => Ok(Variant::ThreePlayers),
+            "four" => Ok(Variant::FourPlayers),
+            _ => Err(SechsUndSechzigError::VariantParseError),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn returns_correct_number_of_players_for_variant() {
+        assert_eq!(2, Variant::TwoPlayers.number_of_players());
         assert_eq!(3, Variant::ThreePlayers.number_of_players());
         assert_eq!(4, Variant::FourPlayers.number_of_players());
     }
 
     #[test]
     fn returns_correct_number_of_cards_per_player_for_variant() {
+        assert_eq!(6, Variant::TwoPlayers.cards_per_player());
         assert_eq!(8, Variant::ThreePlayers.cards_per_player());
         assert_eq!(6, Variant::FourPlayers.cards_per_player());
     }
+
+    #[test]
+    fn only_two_players_leave_talon() {
+        assert_eq!(12, Variant::TwoPlayers.talon_size());
+        assert!(!Variant::ThreePlayers.has_talon());
+        assert!(!Variant::FourPlayers.has_talon());
+    }
+
+    #[test]
+    fn variant_is_parsed_from_number_of_players() {
+        assert_eq!(Ok(Variant::TwoPlayers), "two".parse());
+        assert_eq!(Ok(Variant::FourPlayers), "four".parse());
+        assert_eq!(
+            Err(SechsUndSechzigError::VariantParseError),
+            "five".parse::<Variant>()
+        );
+    }
 }
@@ -44,7 +44,7 @@ impl State<GuessANumber> for GuessANumberState {
     }
 
     fn move_reducer(&self, mv: u32) -> Result<GuessANumberState, GuessANumberError> {
-        if mv <= 0 || mv > self.max_number {
+        if mv == 0 || mv > self.max_number {
             Err(GuessANumberError::OutOfBounds)
         } else if self.last_guess_ordering == Some(Ordering::Equal) {
             Err(GuessANumberError::AleradyFinished)
@@ -56,6 +56,14 @@ impl State<GuessANumber> for GuessANumberState {
             })
         }
     }
+
+    fn legal_moves(&self) -> Vec<u32> {
+        if self.last_guess_ordering == Some(Ordering::Equal) {
+            vec![]
+        } else {
+            (1..=self.max_number).collect()
+        }
+    }
 }
 
 struct GuessANumberView(Option<Ordering>);
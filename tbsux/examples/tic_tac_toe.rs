@@ -3,10 +3,16 @@ use std::{
     fmt::{self, Display},
 };
 
-use playered::View;
-use tbsux::{cli, playered, prelude::*};
+use playered::{Outcome, View};
+use tbsux::{
+    cli,
+    mcts::{Mcts, MctsConfig},
+    negamax::{no_evaluation, Negamax, NegamaxConfig},
+    playered,
+    prelude::*,
+};
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 enum Symbol {
     Cross,
     Circle,
@@ -20,6 +26,14 @@ impl Symbol {
             Circle => Cross,
         }
     }
+
+    fn player(&self) -> playered::Player {
+        use Symbol::*;
+        match self {
+            Cross => 0,
+            Circle => 1,
+        }
+    }
 }
 
 impl fmt::Display for Symbol {
@@ -36,7 +50,7 @@ impl fmt::Display for Symbol {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 struct Field(Option<Symbol>);
 
 impl fmt::Display for Field {
@@ -52,7 +66,7 @@ impl fmt::Display for Field {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Hash)]
 struct Board([Field; 9]);
 
 impl Board {
@@ -91,21 +105,16 @@ impl Board {
                 )
             })
             .filter(|(_, winning)| *winning)
-            .map(|(sample, _)| {
-                if let Field(Some(symbol)) = self.0[sample] {
-                    Some(symbol)
-                } else {
-                    None
-                }
-            })
-            .filter(|opt| opt.is_some())
-            .map(|opt| opt.unwrap())
-            .next()
+            .find_map(|(sample, _)| self.0[sample].0)
     }
 
     fn filled(&self) -> bool {
         self.0.iter().all(|f| f.0.is_some())
     }
+
+    fn empty_positions(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..9u8).filter(move |position| self.0[*position as usize] == Field(None))
+    }
 }
 
 impl fmt::Display for Board {
@@ -151,6 +160,7 @@ impl playered::Game for TicTacToeGame {
     }
 }
 
+#[derive(Clone, Hash)]
 struct TicTacToeState {
     board: Board,
     current_player: Symbol,
@@ -184,6 +194,14 @@ impl State<TicTacToeGame> for TicTacToeState {
             current_player: self.current_player.other(),
         })
     }
+
+    fn legal_moves(&self) -> Vec<u8> {
+        if self.board.winner().is_some() {
+            vec![]
+        } else {
+            self.board.empty_positions().collect()
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -202,10 +220,7 @@ impl View for TicTacToeView {
     type PlayerView = Self;
 
     fn current_player(&self) -> playered::Player {
-        match self.current_player {
-            Symbol::Cross => 1,
-            Symbol::Circle => 2,
-        }
+        self.current_player.player()
     }
 
     fn player_view(&self, _: playered::Player) -> Self::PlayerView {
@@ -213,6 +228,15 @@ impl View for TicTacToeView {
     }
 }
 
+impl From<TicTacToeView> for TicTacToeState {
+    fn from(view: TicTacToeView) -> TicTacToeState {
+        TicTacToeState {
+            board: view.board,
+            current_player: view.current_player,
+        }
+    }
+}
+
 #[derive(Debug)]
 enum TicTacToeError {
     OutOfBoard,
@@ -242,7 +266,109 @@ impl Display for TicTacToeResult {
     }
 }
 
+impl Outcome for TicTacToeResult {
+    fn payoff(&self, player: playered::Player) -> f64 {
+        use TicTacToeResult::*;
+        match self {
+            Victory(symbol) if symbol.player() == player => 1.0,
+            Victory(_) => 0.0,
+            Tie => 0.5,
+        }
+    }
+}
+
+/// Lets MCTS play against itself, printing statistics of every considered move
+fn mcts_self_play() {
+    let mut mcts = Mcts::with_random_seed(MctsConfig::default());
+    let mut state = TicTacToeGame.initial_state();
+    let result = loop {
+        match state.progress_report() {
+            ProgressReport::Finished(result) => break result,
+            ProgressReport::InProgress(view) => {
+                println!("{}", view);
+                let stats = mcts.search::<TicTacToeGame>(&state);
+                for s in &stats {
+                    println!(
+                        "  move {}: {} visits, win rate {:.3}",
+                        s.mv, s.visits, s.win_rate
+                    );
+                }
+                state = state
+                    .move_reducer(stats[0].mv)
+                    .expect("MCTS should pick legal move");
+            }
+        }
+    };
+    println!("{}\n{}", state.board, result);
+}
+
+/// Plays perfect game using negamax, printing value of every position
+fn solve() {
+    let mut negamax = Negamax::new(NegamaxConfig::default(), no_evaluation);
+    let mut state = TicTacToeGame.initial_state();
+    let result = loop {
+        match state.progress_report() {
+            ProgressReport::Finished(result) => break result,
+            ProgressReport::InProgress(view) => {
+                println!("{}", view);
+                let result = negamax.search::<TicTacToeGame>(&state);
+                let best_move = result.best_move.expect("Game is not finished");
+                println!(
+                    "  best move {}, value {}, depth {}",
+                    best_move, result.value, result.depth
+                );
+                state = state
+                    .move_reducer(best_move)
+                    .expect("Negamax should pick legal move");
+            }
+        }
+    };
+    println!("{}\n{}", state.board, result);
+}
+
 fn main() {
-    let game = TicTacToeGame;
-    cli::run_cli(game);
+    match std::env::args().nth(1).as_deref() {
+        Some("mcts") => mcts_self_play(),
+        Some("solve") => solve(),
+        Some("bot") => {
+            let bot = Negamax::new(NegamaxConfig::default(), no_evaluation);
+            let seats = vec![cli::Seat::Human, cli::Seat::Bot(Box::new(bot))];
+            if let Err(err) = cli::run_cli_seated(TicTacToeGame, seats) {
+                println!("Game interrupted: {}", err);
+            }
+        }
+        _ => {
+            cli::run_cli(TicTacToeGame);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(moves: &[u8]) -> TicTacToeState {
+        moves
+            .iter()
+            .fold(TicTacToeGame.initial_state(), |state, mv| {
+                state.move_reducer(*mv).unwrap()
+            })
+    }
+
+    #[test]
+    fn negamax_solves_game_as_tie() {
+        let mut negamax = Negamax::new(NegamaxConfig::default(), no_evaluation);
+        let result = negamax.search::<TicTacToeGame>(&state(&[]));
+        assert_eq!(0.0, result.value);
+        assert!(result.best_move.is_some());
+    }
+
+    #[test]
+    fn negamax_completes_line() {
+        // X holds 0 and 1, O holds 3 and 4
+        let mut negamax = Negamax::new(NegamaxConfig::default(), no_evaluation);
+        let result = negamax.search::<TicTacToeGame>(&state(&[0, 3, 1, 4]));
+        assert_eq!(Some(2), result.best_move);
+        assert_eq!(1.0, result.value);
+    }
 }
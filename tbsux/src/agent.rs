@@ -0,0 +1,160 @@
+use std::{error::Error, fmt};
+
+use rand::prelude::*;
+
+use crate::{
+    core,
+    playered::{self, Player, View},
+    prelude::*,
+};
+
+pub type PlayerView<G> = <<G as core::Game>::View as View>::PlayerView;
+
+/// Picks moves for a single seat, seeing only its player's view
+pub trait Agent<G>
+where
+    G: playered::Game,
+    G::View: View,
+{
+    /// Returns `None` when agent gives up without choosing a move
+    fn select_move(&mut self, view: &PlayerView<G>, legal_moves: &[G::Move]) -> Option<G::Move>;
+}
+
+/// Picks uniformly one of legal moves
+pub struct RandomAgent {
+    rng: StdRng,
+}
+
+impl RandomAgent {
+    pub fn with_seed(seed: u64) -> RandomAgent {
+        RandomAgent {
+            rng: SeedableRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn with_random_seed() -> RandomAgent {
+        RandomAgent::with_seed(thread_rng().next_u64())
+    }
+}
+
+impl<G> Agent<G> for RandomAgent
+where
+    G: playered::Game,
+    G::View: View,
+    G::Move: Clone,
+{
+    fn select_move(&mut self, _: &PlayerView<G>, legal_moves: &[G::Move]) -> Option<G::Move> {
+        legal_moves.choose(&mut self.rng).cloned()
+    }
+}
+
+#[derive(Debug)]
+pub enum RunnerError<E> {
+    MissingAgent(Player),
+    NoMove(Player),
+    IllegalMove(Player, E),
+}
+
+impl<E: fmt::Debug> fmt::Display for RunnerError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl<E: fmt::Debug> Error for RunnerError<E> {}
+
+/// Plays game to the end, asking agent seated at `agents[player]` for every move of `player`
+pub fn run_agents<G>(
+    game: &G,
+    agents: &mut [Box<dyn Agent<G>>],
+) -> Result<G::Result, RunnerError<G::Error>>
+where
+    G: playered::Game,
+    G::View: View,
+{
+    let mut state = game.initial_state();
+    loop {
+        state = match state.progress_report() {
+            ProgressReport::Finished(result) => break Ok(result),
+            ProgressReport::InProgress(view) => play_turn(&state, &view, agents)?,
+        }
+    }
+}
+
+pub(crate) fn play_turn<G>(
+    state: &G::State,
+    view: &G::View,
+    agents: &mut [Box<dyn Agent<G>>],
+) -> Result<G::State, RunnerError<G::Error>>
+where
+    G: playered::Game,
+    G::View: View,
+{
+    let player = view.current_player();
+    let agent = agents
+        .get_mut(player as usize)
+        .ok_or(RunnerError::MissingAgent(player))?;
+    let mv = agent
+        .select_move(&view.player_view(player), &state.legal_moves())
+        .ok_or(RunnerError::NoMove(player))?;
+    state
+        .move_reducer(mv)
+        .map_err(|err| RunnerError::IllegalMove(player, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{Nim, NimError, NimState, NimWinner};
+
+    struct Fixed(u32);
+
+    impl Agent<Nim> for Fixed {
+        fn select_move(&mut self, _: &NimState, _: &[u32]) -> Option<u32> {
+            Some(self.0)
+        }
+    }
+
+    struct Seated;
+
+    impl Agent<Nim> for Seated {
+        fn select_move(&mut self, view: &NimState, _: &[u32]) -> Option<u32> {
+            Some(view.player + 1)
+        }
+    }
+
+    #[test]
+    fn asks_agent_of_current_player() {
+        let mut agents: Vec<Box<dyn Agent<Nim>>> = vec![Box::new(Seated), Box::new(Seated)];
+        // player 0 takes one stone, player 1 takes two stones
+        assert_eq!(NimWinner(1), run_agents(&Nim(3), &mut agents).unwrap());
+        assert_eq!(NimWinner(0), run_agents(&Nim(4), &mut agents).unwrap());
+    }
+
+    #[test]
+    fn random_agents_finish_game() {
+        let mut agents: Vec<Box<dyn Agent<Nim>>> = vec![
+            Box::new(RandomAgent::with_seed(0)),
+            Box::new(RandomAgent::with_seed(1)),
+        ];
+        assert!(run_agents(&Nim(20), &mut agents).is_ok());
+    }
+
+    #[test]
+    fn reports_illegal_move() {
+        let mut agents: Vec<Box<dyn Agent<Nim>>> = vec![Box::new(Fixed(3)), Box::new(Fixed(1))];
+        assert!(matches!(
+            run_agents(&Nim(5), &mut agents),
+            Err(RunnerError::IllegalMove(0, NimError))
+        ));
+    }
+
+    #[test]
+    fn reports_missing_agent() {
+        let mut agents: Vec<Box<dyn Agent<Nim>>> = vec![Box::new(Fixed(1))];
+        assert!(matches!(
+            run_agents(&Nim(5), &mut agents),
+            Err(RunnerError::MissingAgent(1))
+        ));
+    }
+}
@@ -1,6 +1,56 @@
-use std::{fmt::Display, io, io::Write, str::FromStr};
+use std::{error::Error, fmt::Display, io, io::Write, str::FromStr};
 
-use crate::prelude::*;
+use crate::{
+    agent::{Agent, PlayerView, RunnerError},
+    core,
+    playered::{self, Player, View},
+    prelude::*,
+    session::Session,
+};
+
+/// Who decides moves of a single player
+pub enum Seat<G>
+where
+    G: playered::Game,
+    G::View: View,
+{
+    /// Player typing moves on stdin
+    Human,
+    Bot(Box<dyn Agent<G>>),
+}
+
+/// Whether human players can take back moves with `:undo` command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndoPolicy {
+    Disabled,
+    Allowed,
+    /// Every other human player has to agree, bots always do
+    Unanimous,
+}
+
+/// Writes state to file at given path
+pub type SaveFn<G> = Box<dyn Fn(&<G as core::Game>::State, &str) -> Result<(), Box<dyn Error>>>;
+
+pub struct CliOptions<G>
+where
+    G: Game,
+{
+    /// Whether terminal is passed between human players, see [`run_cli_hot_seat`]
+    pub hot_seat: bool,
+    /// Lets human players save game with `:save PATH` command
+    pub save: Option<SaveFn<G>>,
+    pub undo: UndoPolicy,
+}
+
+impl<G: Game> Default for CliOptions<G> {
+    fn default() -> CliOptions<G> {
+        CliOptions {
+            hot_seat: false,
+            save: None,
+            undo: UndoPolicy::Disabled,
+        }
+    }
+}
 
 pub fn run_cli<G>(game: G) -> G::Result
 where
@@ -31,22 +81,290 @@ where
     result
 }
 
+/// Plays game in terminal, routing each turn to seat of current player
+///
+/// Human players see only their own player's view, preceded by moves made
+/// since their previous turn.
+pub fn run_cli_seated<G>(game: G, seats: Vec<Seat<G>>) -> Result<G::Result, RunnerError<G::Error>>
+where
+    G: playered::Game,
+    G::View: View,
+    G::Move: FromStr + Display,
+    G::Result: Display,
+    PlayerView<G>: Display,
+{
+    run_cli_from(game.initial_state(), seats, CliOptions::default())
+}
+
+/// Plays game on a terminal passed between human players
+///
+/// Screen is cleared right after every human move, and before next human
+/// player's view is shown, terminal asks to pass it to that player and waits for Enter.
+pub fn run_cli_hot_seat<G>(game: G, seats: Vec<Seat<G>>) -> Result<G::Result, RunnerError<G::Error>>
+where
+    G: playered::Game,
+    G::View: View,
+    G::Move: FromStr + Display,
+    G::Result: Display,
+    PlayerView<G>: Display,
+{
+    let options = CliOptions {
+        hot_seat: true,
+        ..Default::default()
+    };
+    run_cli_from(game.initial_state(), seats, options)
+}
+
+/// Plays game in terminal starting from `state`, which may come from a saved game
+///
+/// Undo takes back the last move of player asking for it, together with bot moves made
+/// after it. Moves of other human players are taken back only when they all agree to it.
+pub fn run_cli_from<G>(
+    state: G::State,
+    mut seats: Vec<Seat<G>>,
+    options: CliOptions<G>,
+) -> Result<G::Result, RunnerError<G::Error>>
+where
+    G: playered::Game,
+    G::View: View,
+    G::Move: FromStr + Display,
+    G::Result: Display,
+    PlayerView<G>: Display,
+{
+    let mut session: Session<G> = Session::new(state);
+    let mut moves_log = vec![];
+    // length of log before every move, so undo can remove lines of moves taken back
+    let mut log_lengths = vec![];
+    // index of first move in log each player has not seen yet
+    let mut unseen_moves = vec![0; seats.len()];
+    let mut last_human = None;
+    let result = loop {
+        let view = match session.current().progress_report() {
+            ProgressReport::Finished(result) => break result,
+            ProgressReport::InProgress(view) => view,
+        };
+        let player = view.current_player();
+        let player_view = view.player_view(player);
+        match seats.get_mut(player as usize) {
+            Some(Seat::Human) => {
+                clear_screen();
+                if options.hot_seat && last_human != Some(player) {
+                    wait_for_player(player);
+                    clear_screen();
+                }
+                last_human = Some(player);
+
+                for line in &moves_log[unseen_moves[player as usize]..] {
+                    println!("{}", line);
+                }
+                println!("PLAYER {} MOVE\n\n{}", player, player_view);
+                loop {
+                    let mv: G::Move = match retrieve_input() {
+                        Input::Move(mv) => mv,
+                        Input::Save(path) => {
+                            match &options.save {
+                                Some(save) => match save(session.current(), &path) {
+                                    Ok(()) => println!("Game saved to {}", path),
+                                    Err(err) => println!("Could not save game: {}", err),
+                                },
+                                None => println!("Saving is not supported"),
+                            }
+                            continue;
+                        }
+                        Input::Undo => {
+                            if options.undo == UndoPolicy::Disabled {
+                                println!("Undo is not allowed");
+                            } else if options.undo == UndoPolicy::Unanimous
+                                && !others_agree(&seats, player)
+                            {
+                                println!("Undo was not accepted");
+                            } else if let Some(ply) = own_move_ply(
+                                &session,
+                                &seats,
+                                player,
+                                options.undo == UndoPolicy::Unanimous,
+                            ) {
+                                session.jump_to(ply);
+                                moves_log.truncate(log_lengths[ply]);
+                                log_lengths.truncate(ply);
+                                for unseen in unseen_moves.iter_mut() {
+                                    *unseen = (*unseen).min(moves_log.len());
+                                }
+                                moves_log.push(format!("Player {} took back last move", player));
+                                break;
+                            } else {
+                                println!("Nothing to undo");
+                            }
+                            continue;
+                        }
+                    };
+                    let description = format!("Player {} played {}", player, mv);
+                    match session.play(mv) {
+                        Ok(()) => {
+                            log_lengths.truncate(session.ply() - 1);
+                            log_lengths.push(moves_log.len());
+                            moves_log.push(description);
+                            break;
+                        }
+                        Err(err) => println!("Invaild move: {}", err),
+                    }
+                }
+                unseen_moves[player as usize] = moves_log.len();
+                if options.hot_seat {
+                    clear_screen();
+                }
+            }
+            Some(Seat::Bot(agent)) => {
+                let mv = agent
+                    .select_move(&player_view, &session.current().legal_moves())
+                    .ok_or(RunnerError::NoMove(player))?;
+                let description = format!("Player {} played {}", player, mv);
+                session
+                    .play(mv)
+                    .map_err(|err| RunnerError::IllegalMove(player, err))?;
+                log_lengths.truncate(session.ply() - 1);
+                log_lengths.push(moves_log.len());
+                moves_log.push(description);
+            }
+            None => return Err(RunnerError::MissingAgent(player)),
+        };
+    };
+    clear_screen();
+    let last_moves = unseen_moves.into_iter().min().unwrap_or(0);
+    for line in &moves_log[last_moves..] {
+        println!("{}", line);
+    }
+    println!("Game finished, result: {}", result);
+    Ok(result)
+}
+
+/// Ply of latest move of `asking` player, when moves made after it can be taken back
+///
+/// Moves of bots can always be taken back, moves of other human players only
+/// when `others_agreed`.
+fn own_move_ply<G>(
+    session: &Session<G>,
+    seats: &[Seat<G>],
+    asking: Player,
+    others_agreed: bool,
+) -> Option<usize>
+where
+    G: playered::Game,
+    G::View: View,
+{
+    for ply in (0..session.ply()).rev() {
+        let player = match session.state_at(ply)?.progress_report() {
+            ProgressReport::InProgress(view) => view.current_player(),
+            ProgressReport::Finished(_) => return None,
+        };
+        if player == asking {
+            return Some(ply);
+        }
+        if !others_agreed && matches!(seats.get(player as usize), Some(Seat::Human)) {
+            return None;
+        }
+    }
+    None
+}
+
+fn others_agree<G>(seats: &[Seat<G>], asking: Player) -> bool
+where
+    G: playered::Game,
+    G::View: View,
+{
+    seats.iter().enumerate().all(|(player, seat)| {
+        player as Player == asking
+            || matches!(seat, Seat::Bot(_))
+            || read_line(&format!(
+                "Player {} wants to undo, player {} agrees? [y/n] ",
+                asking, player
+            ))
+            .eq_ignore_ascii_case("y")
+    })
+}
+
+fn wait_for_player(player: playered::Player) {
+    print!("Pass terminal to player {}, then press Enter", player);
+    io::stdout().flush().expect("Could not flush stdout");
+    io::stdin()
+        .read_line(&mut String::new())
+        .expect("Could not read line from stdin");
+}
+
 fn clear_screen() {
     print!("{esc}c", esc = 27 as char);
 }
 
+fn read_line(prompt: &str) -> String {
+    let mut buf = String::new();
+    print!("{}", prompt);
+    io::stdout().flush().expect("Could not flush stdout");
+    io::stdin()
+        .read_line(&mut buf)
+        .expect("Could not read line from stdin");
+    buf.trim().to_owned()
+}
+
 fn retrieve_move<M: FromStr>() -> M {
     loop {
-        let mut buf = String::new();
-        print!("MOVE> ");
-        io::stdout().flush().expect("Could not flush stdout");
-        io::stdin()
-            .read_line(&mut buf)
-            .expect("Could not read line from stdin");
-
-        match buf.trim().parse() {
+        match read_line("MOVE> ").parse() {
             Ok(mv) => break mv,
             Err(_) => println!("Could not parse move"),
         }
     }
 }
+
+/// Line typed by human player, commands start with a colon
+enum Input<M> {
+    Move(M),
+    Save(String),
+    Undo,
+}
+
+fn retrieve_input<M: FromStr>() -> Input<M> {
+    loop {
+        let line = read_line("MOVE> ");
+        if let Some(command) = line.strip_prefix(':') {
+            let mut words = command.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some("save"), Some(path)) => break Input::Save(path.to_owned()),
+                (Some("undo"), None) => break Input::Undo,
+                _ => println!("Unknown command, available: :save PATH, :undo"),
+            }
+        } else {
+            match line.parse() {
+                Ok(mv) => break Input::Move(mv),
+                Err(_) => println!("Could not parse move"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{agent::RandomAgent, testing::Nim};
+
+    /// Session of Nim, in which players take one stone `moves` times
+    fn session(moves: usize) -> Session<Nim> {
+        let mut session = Session::new(Nim(10).initial_state());
+        for _ in 0..moves {
+            session.play(1).unwrap();
+        }
+        session
+    }
+
+    #[test]
+    fn undo_takes_back_own_move_with_bot_moves_after_it() {
+        let seats = vec![Seat::Human, Seat::Bot(Box::new(RandomAgent::with_seed(0)))];
+        assert_eq!(Some(2), own_move_ply(&session(4), &seats, 0, false));
+        assert_eq!(None, own_move_ply(&session(0), &seats, 0, false));
+    }
+
+    #[test]
+    fn undo_takes_back_moves_of_other_humans_only_when_they_agree() {
+        let seats: Vec<Seat<Nim>> = vec![Seat::Human, Seat::Human];
+        assert_eq!(None, own_move_ply(&session(4), &seats, 0, false));
+        assert_eq!(Some(2), own_move_ply(&session(4), &seats, 0, true));
+    }
+}
@@ -0,0 +1,358 @@
+use std::{
+    cell::Cell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    agent::{Agent, RandomAgent, RunnerError},
+    playered::{self, Player, View},
+    prelude::*,
+};
+
+/// Source of current time, replaceable to simulate passing time in tests
+pub trait Clock {
+    /// Time passed since an arbitrary, fixed moment
+    fn now(&self) -> Duration;
+}
+
+pub struct SystemClock {
+    started: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock {
+            started: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+/// Clock moving only when told to, clones share the same time
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Rc<Cell<Duration>>,
+}
+
+impl ManualClock {
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeControl {
+    /// Time every player starts with
+    pub initial: Duration,
+    /// Time added to player's clock after each of their moves made in time
+    pub increment: Duration,
+}
+
+/// Remaining time of every player, with at most one clock running
+pub struct PlayerClocks<C> {
+    clock: C,
+    control: TimeControl,
+    remaining: Vec<Duration>,
+    running: Option<(Player, Duration)>,
+}
+
+impl<C: Clock> PlayerClocks<C> {
+    pub fn new(clock: C, control: TimeControl, players: u32) -> PlayerClocks<C> {
+        PlayerClocks {
+            clock,
+            control,
+            remaining: vec![control.initial; players as usize],
+            running: None,
+        }
+    }
+
+    /// Starts clock of `player`, stopping one running before without increment
+    pub fn start(&mut self, player: Player) {
+        if let Some((running, _)) = self.running {
+            self.remaining[running as usize] = self.remaining(running);
+        }
+        self.running = Some((player, self.clock.now()));
+    }
+
+    /// Time left to `player`, including time used by their running clock
+    pub fn remaining(&self, player: Player) -> Duration {
+        let remaining = self.remaining[player as usize];
+        match self.running {
+            Some((running, started)) if running == player => {
+                remaining.saturating_sub(self.clock.now() - started)
+            }
+            _ => remaining,
+        }
+    }
+
+    /// Player whose running clock has run out
+    pub fn expired(&self) -> Option<Player> {
+        self.running
+            .map(|(player, _)| player)
+            .filter(|player| self.remaining(*player).is_zero())
+    }
+
+    /// Stops running clock, adding increment if its player made it in time
+    ///
+    /// Returns `false` when time ran out, leaving player's clock at zero.
+    pub fn stop(&mut self) -> bool {
+        match self.running.take() {
+            Some((player, started)) => {
+                let used = self.clock.now() - started;
+                let remaining = &mut self.remaining[player as usize];
+                if used < *remaining {
+                    *remaining = *remaining - used + self.control.increment;
+                    true
+                } else {
+                    *remaining = Duration::ZERO;
+                    false
+                }
+            }
+            None => true,
+        }
+    }
+}
+
+/// What happens when player runs out of time
+pub enum TimeoutAction<G>
+where
+    G: playered::Game,
+    G::View: View,
+{
+    /// Player loses the game
+    Forfeit,
+    /// One of legal moves, drawn with given seed, is played instead of player's one
+    RandomMove(u64),
+    /// Bot moves instead of player
+    Bot(Box<dyn Agent<G>>),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TimedResult<R> {
+    Finished(R),
+    /// Player ran out of time with `TimeoutAction::Forfeit`
+    Forfeited(Player),
+}
+
+/// Plays game to the end like [`run_agents`](crate::agent::run_agents), timing every move
+///
+/// Agents cannot be interrupted, so time used by a move is checked after agent
+/// returns it. When a clock has run out, the move is replaced according to
+/// `on_timeout`. Player's clock then stays at zero, so every following move of
+/// that player is replaced too, without asking their agent.
+pub fn run_timed<G, C>(
+    game: &G,
+    agents: &mut [Box<dyn Agent<G>>],
+    clocks: &mut PlayerClocks<C>,
+    on_timeout: &mut TimeoutAction<G>,
+) -> Result<TimedResult<G::Result>, RunnerError<G::Error>>
+where
+    G: playered::Game,
+    G::View: View,
+    G::Move: Clone,
+    C: Clock,
+{
+    let mut random = None;
+    let mut state = game.initial_state();
+    loop {
+        let view = match state.progress_report() {
+            ProgressReport::Finished(result) => break Ok(TimedResult::Finished(result)),
+            ProgressReport::InProgress(view) => view,
+        };
+        let player = view.current_player();
+        let player_view = view.player_view(player);
+        let legal_moves = state.legal_moves();
+
+        let agent = agents
+            .get_mut(player as usize)
+            .ok_or(RunnerError::MissingAgent(player))?;
+        let mut mv = None;
+        // agent of player out of time could block the game, e.g. human who walked away
+        let in_time = !clocks.remaining(player).is_zero() && {
+            clocks.start(player);
+            mv = agent.select_move(&player_view, &legal_moves);
+            clocks.stop()
+        };
+        if !in_time {
+            mv = match on_timeout {
+                TimeoutAction::Forfeit => break Ok(TimedResult::Forfeited(player)),
+                TimeoutAction::RandomMove(seed) => {
+                    let random = random.get_or_insert_with(|| RandomAgent::with_seed(*seed));
+                    Agent::<G>::select_move(random, &player_view, &legal_moves)
+                }
+                TimeoutAction::Bot(agent) => agent.select_move(&player_view, &legal_moves),
+            };
+        }
+        let mv = mv.ok_or(RunnerError::NoMove(player))?;
+        state = state
+            .move_reducer(mv)
+            .map_err(|err| RunnerError::IllegalMove(player, err))?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{Nim, NimState, NimWinner};
+
+    fn control() -> TimeControl {
+        TimeControl {
+            initial: Duration::from_secs(10),
+            increment: Duration::from_secs(1),
+        }
+    }
+
+    /// Takes always the same number of stones, thinking for given time
+    struct Slow {
+        clock: ManualClock,
+        thinking: Duration,
+        take: u32,
+    }
+
+    impl Agent<Nim> for Slow {
+        fn select_move(&mut self, _: &NimState, _: &[u32]) -> Option<u32> {
+            self.clock.advance(self.thinking);
+            Some(self.take)
+        }
+    }
+
+    fn agents(clock: &ManualClock, thinking: [u64; 2]) -> Vec<Box<dyn Agent<Nim>>> {
+        thinking
+            .iter()
+            .map(|seconds| {
+                Box::new(Slow {
+                    clock: clock.clone(),
+                    thinking: Duration::from_secs(*seconds),
+                    take: 1,
+                }) as Box<dyn Agent<Nim>>
+            })
+            .collect()
+    }
+
+    #[test]
+    fn clock_adds_increment_only_to_moves_in_time() {
+        let clock = ManualClock::default();
+        let mut clocks = PlayerClocks::new(clock.clone(), control(), 2);
+        clocks.start(0);
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(Duration::from_secs(6), clocks.remaining(0));
+        assert!(clocks.stop());
+        assert_eq!(Duration::from_secs(7), clocks.remaining(0));
+
+        clocks.start(1);
+        clock.advance(Duration::from_secs(12));
+        assert_eq!(Some(1), clocks.expired());
+        assert!(!clocks.stop());
+        assert_eq!(Duration::ZERO, clocks.remaining(1));
+        assert_eq!(None, clocks.expired());
+    }
+
+    #[test]
+    fn increments_keep_player_in_time() {
+        let clock = ManualClock::default();
+        let mut clocks = PlayerClocks::new(clock.clone(), control(), 2);
+        // both players use 2s per move, getting 1s back, over 5 moves each
+        let result = run_timed(
+            &Nim(10),
+            &mut agents(&clock, [2, 2]),
+            &mut clocks,
+            &mut TimeoutAction::Forfeit,
+        );
+        assert_eq!(TimedResult::Finished(NimWinner(1)), result.unwrap());
+        assert_eq!(Duration::from_secs(5), clocks.remaining(0));
+    }
+
+    #[test]
+    fn slow_player_forfeits() {
+        let clock = ManualClock::default();
+        let mut clocks = PlayerClocks::new(clock.clone(), control(), 2);
+        let result = run_timed(
+            &Nim(10),
+            &mut agents(&clock, [1, 4]),
+            &mut clocks,
+            &mut TimeoutAction::Forfeit,
+        );
+        assert_eq!(TimedResult::Forfeited(1), result.unwrap());
+    }
+
+    #[test]
+    fn substitute_moves_for_slow_player() {
+        let clock = ManualClock::default();
+        let mut clocks = PlayerClocks::new(clock.clone(), control(), 2);
+        // player 0 always takes one stone, bot replacing player 1 takes two,
+        // so player 1 wins only if the bot took over
+        let mut on_timeout = TimeoutAction::Bot(Box::new(Slow {
+            clock: clock.clone(),
+            thinking: Duration::ZERO,
+            take: 2,
+        }));
+        let result = run_timed(
+            &Nim(9),
+            &mut agents(&clock, [1, 20]),
+            &mut clocks,
+            &mut on_timeout,
+        );
+        assert_eq!(TimedResult::Finished(NimWinner(1)), result.unwrap());
+
+        let mut clocks = PlayerClocks::new(clock.clone(), control(), 2);
+        let mut on_timeout = TimeoutAction::RandomMove(0);
+        let result = run_timed(
+            &Nim(9),
+            &mut agents(&clock, [1, 20]),
+            &mut clocks,
+            &mut on_timeout,
+        );
+        assert!(matches!(result, Ok(TimedResult::Finished(_))));
+    }
+
+    #[test]
+    fn player_out_of_time_is_not_asked_for_moves() {
+        let clock = ManualClock::default();
+        let mut clocks = PlayerClocks::new(clock.clone(), control(), 2);
+        let started = clock.now();
+        run_timed(
+            &Nim(20),
+            &mut agents(&clock, [0, 20]),
+            &mut clocks,
+            &mut TimeoutAction::RandomMove(0),
+        )
+        .unwrap();
+        // only the first move of slow player was waited for
+        assert_eq!(Duration::from_secs(20), clock.now() - started);
+    }
+
+    #[test]
+    fn random_substitute_moves_are_reproducible() {
+        let play = || {
+            let clock = ManualClock::default();
+            let mut clocks = PlayerClocks::new(clock.clone(), control(), 2);
+            let mut agents = agents(&clock, [20, 20]);
+            run_timed(
+                &Nim(30),
+                &mut agents,
+                &mut clocks,
+                &mut TimeoutAction::RandomMove(5),
+            )
+            .unwrap()
+        };
+        assert_eq!(play(), play());
+    }
+}
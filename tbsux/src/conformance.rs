@@ -0,0 +1,280 @@
+//! Checking that a game implementation follows rules every game should obey
+//!
+//! Harness plays many random games, choosing uniformly among legal moves, and
+//! after every move checks that:
+//! - game in progress has legal moves, each of them accepted by `move_reducer`,
+//! - a move rejected by `move_reducer` leaves the state usable,
+//! - player to move is the same in cloned state and in repeated reports,
+//! - game finishes within configured number of moves,
+//! - game specific invariants hold.
+//!
+//! Panics are caught and reported like any other violation, together with seed
+//! and moves leading to them, so they can be reproduced.
+
+use std::{
+    error, fmt,
+    panic::{self, AssertUnwindSafe},
+};
+
+use rand::prelude::*;
+
+use crate::{
+    playered::{self, View},
+    prelude::*,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConformanceConfig {
+    pub games: u32,
+    /// Moves after which unfinished game is considered endless
+    pub max_moves: usize,
+    /// Seed of first game, following games use consecutive ones
+    pub seed: u64,
+}
+
+impl Default for ConformanceConfig {
+    fn default() -> ConformanceConfig {
+        ConformanceConfig {
+            games: 1000,
+            max_moves: 10_000,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    Panic(String),
+    NotFinished,
+    NoLegalMoves,
+    /// Move listed as legal was rejected with given error
+    LegalMoveRejected(String),
+    /// State stopped accepting legal moves after rejecting an illegal one
+    RejectedMoveBrokeState,
+    UnstableCurrentPlayer,
+    Invariant(String),
+}
+
+/// Violation found in game created from `seed`, after playing `moves`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConformanceFailure<M> {
+    pub seed: u64,
+    pub moves: Vec<M>,
+    pub violation: Violation,
+}
+
+impl<M: fmt::Debug> fmt::Display for ConformanceFailure<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl<M: fmt::Debug> error::Error for ConformanceFailure<M> {}
+
+/// Plays random games created by `new_game`, stopping at first violation
+///
+/// `invariant` is checked for every state reached, including the initial one,
+/// and returns description of what is wrong. Illegal moves are taken from moves
+/// which were legal earlier in the same game.
+pub fn check_conformance<G>(
+    new_game: impl Fn(u64) -> G,
+    invariant: impl Fn(&G::State) -> Result<(), String>,
+    config: ConformanceConfig,
+) -> Result<(), ConformanceFailure<G::Move>>
+where
+    G: playered::Game,
+    G::View: View,
+    G::State: Clone,
+    G::Move: Clone + PartialEq,
+{
+    for game in 0..config.games {
+        let seed = config.seed.wrapping_add(game as u64);
+        let mut moves = vec![];
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            play_random_game(&new_game(seed), &invariant, config, seed, &mut moves)
+        }));
+        let violation = match result {
+            Ok(Ok(())) => continue,
+            Ok(Err(violation)) => violation,
+            Err(payload) => Violation::Panic(panic_message(payload)),
+        };
+        return Err(ConformanceFailure {
+            seed,
+            moves,
+            violation,
+        });
+    }
+    Ok(())
+}
+
+fn play_random_game<G>(
+    game: &G,
+    invariant: &impl Fn(&G::State) -> Result<(), String>,
+    config: ConformanceConfig,
+    seed: u64,
+    moves: &mut Vec<G::Move>,
+) -> Result<(), Violation>
+where
+    G: playered::Game,
+    G::View: View,
+    G::State: Clone,
+    G::Move: Clone + PartialEq,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    // moves legal at some point of the game, most of them illegal later
+    let mut seen_moves: Vec<G::Move> = vec![];
+    let mut state = game.initial_state();
+    loop {
+        invariant(&state).map_err(Violation::Invariant)?;
+        let current = match state.progress_report() {
+            ProgressReport::Finished(_) => return Ok(()),
+            ProgressReport::InProgress(view) => view.current_player(),
+        };
+        if moves.len() >= config.max_moves {
+            return Err(Violation::NotFinished);
+        }
+        if !is_current_player::<G>(&state.clone(), current)
+            || !is_current_player::<G>(&state, current)
+        {
+            return Err(Violation::UnstableCurrentPlayer);
+        }
+
+        let legal_moves = state.legal_moves();
+        if legal_moves.is_empty() {
+            return Err(Violation::NoLegalMoves);
+        }
+        for mv in &legal_moves {
+            if let Err(err) = state.move_reducer(mv.clone()) {
+                return Err(Violation::LegalMoveRejected(err.to_string()));
+            }
+            if !seen_moves.contains(mv) {
+                seen_moves.push(mv.clone());
+            }
+        }
+
+        let illegal_moves: Vec<_> = seen_moves
+            .iter()
+            .filter(|mv| !legal_moves.contains(mv))
+            .collect();
+        if let Some(illegal) = illegal_moves.choose(&mut rng) {
+            if state.move_reducer((*illegal).clone()).is_ok() {
+                // accepting moves not listed as legal is allowed, rejecting them must be harmless
+            } else if state.legal_moves() != legal_moves
+                || !is_current_player::<G>(&state, current)
+                || state.move_reducer(legal_moves[0].clone()).is_err()
+            {
+                return Err(Violation::RejectedMoveBrokeState);
+            }
+        }
+
+        let mv = legal_moves
+            .choose(&mut rng)
+            .expect("Legal moves are not empty")
+            .clone();
+        state = state
+            .move_reducer(mv.clone())
+            .map_err(|err| Violation::LegalMoveRejected(err.to_string()))?;
+        moves.push(mv);
+    }
+}
+
+fn is_current_player<G>(state: &G::State, player: playered::Player) -> bool
+where
+    G: playered::Game,
+    G::View: View,
+{
+    match state.progress_report() {
+        ProgressReport::InProgress(view) => view.current_player() == player,
+        ProgressReport::Finished(_) => false,
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "panic without message".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{Nim, NimError, NimState, NimView};
+
+    #[test]
+    fn nim_conforms() {
+        let config = ConformanceConfig {
+            games: 100,
+            ..ConformanceConfig::default()
+        };
+        assert_eq!(Ok(()), check_conformance(|_| Nim(20), |_| Ok(()), config));
+    }
+
+    #[test]
+    fn reports_broken_invariant_with_moves_leading_to_it() {
+        let failure = check_conformance(
+            |_| Nim(20),
+            |state: &NimState| match state.stones {
+                17 => Err("17 stones".to_owned()),
+                _ => Ok(()),
+            },
+            ConformanceConfig::default(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            Violation::Invariant("17 stones".to_owned()),
+            failure.violation
+        );
+        assert_eq!(3, failure.moves.iter().sum::<u32>());
+    }
+
+    /// Nim which never ends, because taking stones from an empty heap panics
+    struct BrokenNim;
+
+    impl Game for BrokenNim {
+        type State = NimState;
+        type Move = u32;
+        type Result = ();
+        type View = NimView;
+        type Error = NimError;
+
+        fn initial_state(&self) -> NimState {
+            Nim(3).initial_state()
+        }
+    }
+
+    impl playered::Game for BrokenNim {
+        fn no_of_players(&self) -> u32 {
+            2
+        }
+    }
+
+    impl State<BrokenNim> for NimState {
+        fn progress_report(&self) -> ProgressReport<BrokenNim> {
+            ProgressReport::InProgress(NimView(self.clone()))
+        }
+
+        fn move_reducer(&self, mv: u32) -> Result<NimState, NimError> {
+            Ok(NimState {
+                stones: self.stones.checked_sub(mv).expect("Heap is empty"),
+                player: 1 - self.player,
+            })
+        }
+
+        fn legal_moves(&self) -> Vec<u32> {
+            vec![1]
+        }
+    }
+
+    #[test]
+    fn catches_panics() {
+        let failure =
+            check_conformance(|_| BrokenNim, |_| Ok(()), ConformanceConfig::default()).unwrap_err();
+        assert_eq!(0, failure.seed);
+        assert_eq!(vec![1, 1, 1], failure.moves);
+        assert!(matches!(failure.violation, Violation::Panic(_)));
+    }
+}
@@ -27,4 +27,10 @@ where
 {
     fn progress_report(&self) -> ProgressReport<G>;
     fn move_reducer(&self, mv: <G as Game>::Move) -> Result<Self, <G as Game>::Error>;
+
+    /// Lists every move that `move_reducer` accepts in current state.
+    ///
+    /// Returns an empty list only when game is finished, agents and search
+    /// algorithms rely on it to tell finished games apart.
+    fn legal_moves(&self) -> Vec<<G as Game>::Move>;
 }
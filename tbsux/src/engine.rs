@@ -0,0 +1,428 @@
+//! Playing with external engines, running as separate processes talking over stdin and stdout
+//!
+//! Protocol is line based, in the spirit of UCI. Runner starts with `tbsux 1`,
+//! and engine introduces itself with `id name <name>`, announces every option
+//! it supports with `option <name> <default>` and finishes with `ready`.
+//! Runner may then change options with `setoption <name> <value>`.
+//!
+//! Before first move of every game runner sends `newgame`. Each move is
+//! requested with:
+//! - `position <n>` followed by `n` lines of player's view,
+//! - `moves <n>` followed by `n` legal moves, one per line,
+//! - `go <id> <milliseconds>` with time engine has for the move.
+//!
+//! Engine answers with `bestmove <id> <move>`, using the same syntax as moves
+//! it got. Lines starting with `info` are ignored, so engines can use them
+//! for debugging. When engine does not answer in time, runner sends `stop`
+//! and ignores answer to that `go`, which it recognizes by its `id`. Runner
+//! finishes with `quit`.
+
+use std::{
+    error, fmt,
+    io::{self, BufRead, BufReader, Read, Write},
+    process::{Child, Command, Stdio},
+    str::FromStr,
+    sync::mpsc::{channel, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use rand::prelude::*;
+
+use crate::{
+    agent::{Agent, PlayerView},
+    playered::{self, View},
+};
+
+pub const PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum EngineError {
+    Io(io::Error),
+    /// Engine sent a line it should not send at this point
+    Protocol(String),
+    UnknownOption(String),
+    Timeout,
+    /// Engine closed its output
+    Closed,
+    /// Engine chose a move which could not be parsed or is not legal
+    InvalidMove(String),
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl error::Error for EngineError {}
+
+impl From<io::Error> for EngineError {
+    fn from(err: io::Error) -> EngineError {
+        EngineError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineConfig {
+    /// Time engine has to start and introduce itself
+    pub startup_time: Duration,
+    /// Time engine has for every move
+    pub move_time: Duration,
+    /// Additional time for answer to arrive, after which move is considered late
+    pub grace: Duration,
+    /// Options set after handshake, every one of them has to be supported by engine
+    pub options: Vec<(String, String)>,
+}
+
+impl Default for EngineConfig {
+    fn default() -> EngineConfig {
+        EngineConfig {
+            startup_time: Duration::from_secs(5),
+            move_time: Duration::from_secs(1),
+            grace: Duration::from_millis(200),
+            options: vec![],
+        }
+    }
+}
+
+/// Agent asking external engine for moves
+///
+/// Views and moves are sent using their `Display`, and moves chosen by engine
+/// are read with `FromStr`. When engine fails to choose a legal move in time,
+/// agent gives up the move, and the reason is kept in [`last_error`](ExternalAgent::last_error).
+pub struct ExternalAgent {
+    name: String,
+    options: Vec<(String, String)>,
+    config: EngineConfig,
+    writer: Box<dyn Write + Send>,
+    lines: Receiver<String>,
+    child: Option<Child>,
+    next_id: u64,
+    in_game: bool,
+    last_error: Option<EngineError>,
+}
+
+impl ExternalAgent {
+    /// Starts `command` and talks to it through its stdin and stdout
+    pub fn spawn(mut command: Command, config: EngineConfig) -> Result<ExternalAgent, EngineError> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("Stdin is piped");
+        let stdout = child.stdout.take().expect("Stdout is piped");
+        let mut agent = ExternalAgent::connect(stdout, stdin, config)?;
+        agent.child = Some(child);
+        Ok(agent)
+    }
+
+    /// Talks to engine already running on other end of `reader` and `writer`
+    pub fn connect(
+        reader: impl Read + Send + 'static,
+        writer: impl Write + Send + 'static,
+        config: EngineConfig,
+    ) -> Result<ExternalAgent, EngineError> {
+        let (sender, lines) = channel();
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    return;
+                }
+            }
+        });
+
+        let mut agent = ExternalAgent {
+            name: String::new(),
+            options: vec![],
+            config,
+            writer: Box::new(writer),
+            lines,
+            child: None,
+            next_id: 0,
+            in_game: false,
+            last_error: None,
+        };
+        agent.handshake()?;
+        Ok(agent)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Options announced by engine, with their default values
+    pub fn options(&self) -> &[(String, String)] {
+        &self.options
+    }
+
+    /// Why the last move was given up, if it was
+    pub fn last_error(&self) -> Option<&EngineError> {
+        self.last_error.as_ref()
+    }
+
+    /// Makes engine start a new game before next move, so the same process can play many games
+    pub fn new_game(&mut self) {
+        self.in_game = false;
+    }
+
+    fn handshake(&mut self) -> Result<(), EngineError> {
+        self.send(&format!("tbsux {}", PROTOCOL_VERSION))?;
+        let deadline = Instant::now() + self.config.startup_time;
+        loop {
+            let line = self.receive(deadline)?;
+            let mut words = line.splitn(3, ' ');
+            match (words.next(), words.next(), words.next()) {
+                (Some("id"), Some("name"), Some(name)) => self.name = name.to_owned(),
+                (Some("option"), Some(name), default) => self
+                    .options
+                    .push((name.to_owned(), default.unwrap_or("").to_owned())),
+                (Some("ready"), None, None) => break,
+                (Some("info"), _, _) => (),
+                _ => return Err(EngineError::Protocol(line)),
+            }
+        }
+
+        for (name, value) in self.config.options.clone() {
+            if !self.options.iter().any(|(option, _)| *option == name) {
+                return Err(EngineError::UnknownOption(name));
+            }
+            self.send(&format!("setoption {} {}", name, value))?;
+        }
+        Ok(())
+    }
+
+    fn request_move<M>(
+        &mut self,
+        view: &impl fmt::Display,
+        legal_moves: &[M],
+    ) -> Result<M, EngineError>
+    where
+        M: fmt::Display + FromStr + PartialEq,
+    {
+        if !self.in_game {
+            self.send("newgame")?;
+            self.in_game = true;
+        }
+        let view = view.to_string();
+        let view: Vec<_> = view.lines().collect();
+        self.send(&format!("position {}", view.len()))?;
+        for line in view {
+            self.send(line)?;
+        }
+        self.send(&format!("moves {}", legal_moves.len()))?;
+        for mv in legal_moves {
+            self.send(&mv.to_string())?;
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let move_time = self.config.move_time;
+        self.send(&format!("go {} {}", id, move_time.as_millis()))?;
+        let deadline = Instant::now() + move_time + self.config.grace;
+        let answer = loop {
+            let line = match self.receive(deadline) {
+                Err(EngineError::Timeout) => {
+                    self.send("stop")?;
+                    return Err(EngineError::Timeout);
+                }
+                line => line?,
+            };
+            let mut words = line.splitn(3, ' ');
+            match (words.next(), words.next(), words.next()) {
+                (Some("bestmove"), Some(answered), Some(mv)) if answered == id.to_string() => {
+                    break mv.to_owned()
+                }
+                // late answers to earlier moves
+                (Some("bestmove"), _, _) | (Some("info"), _, _) => (),
+                _ => return Err(EngineError::Protocol(line)),
+            }
+        };
+        answer
+            .parse()
+            .ok()
+            .filter(|mv| legal_moves.contains(mv))
+            .ok_or(EngineError::InvalidMove(answer))
+    }
+
+    fn send(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.writer, "{}", line)?;
+        self.writer.flush()
+    }
+
+    fn receive(&mut self, deadline: Instant) -> Result<String, EngineError> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        self.lines.recv_timeout(timeout).map_err(|err| match err {
+            RecvTimeoutError::Timeout => EngineError::Timeout,
+            RecvTimeoutError::Disconnected => EngineError::Closed,
+        })
+    }
+}
+
+impl<G> Agent<G> for ExternalAgent
+where
+    G: playered::Game,
+    G::View: View,
+    PlayerView<G>: fmt::Display,
+    G::Move: fmt::Display + FromStr + PartialEq,
+{
+    fn select_move(&mut self, view: &PlayerView<G>, legal_moves: &[G::Move]) -> Option<G::Move> {
+        match self.request_move(view, legal_moves) {
+            Ok(mv) => {
+                self.last_error = None;
+                Some(mv)
+            }
+            Err(err) => {
+                self.last_error = Some(err);
+                None
+            }
+        }
+    }
+}
+
+impl Drop for ExternalAgent {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        if let Some(child) = &mut self.child {
+            for _ in 0..10 {
+                if let Ok(Some(_)) = child.try_wait() {
+                    return;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Engine choosing random legal moves, working with any game
+///
+/// Supports options `seed`, initializing its random number generator, and
+/// `delay`, making it think for given number of milliseconds before every move.
+/// Returns when runner sends `quit` or closes `input`.
+pub fn run_reference_engine(input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    let mut lines = input.lines();
+    let mut next_line = move || lines.next().transpose();
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut delay = Duration::ZERO;
+    let mut moves: Vec<String> = vec![];
+
+    while let Some(line) = next_line()? {
+        let words: Vec<_> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["tbsux", _] => {
+                writeln!(output, "id name reference")?;
+                writeln!(output, "option seed 0")?;
+                writeln!(output, "option delay 0")?;
+                writeln!(output, "ready")?;
+            }
+            ["setoption", "seed", seed] => {
+                let seed = seed.parse().unwrap_or_default();
+                rng = StdRng::seed_from_u64(seed);
+            }
+            ["setoption", "delay", delay_ms] => {
+                delay = Duration::from_millis(delay_ms.parse().unwrap_or_default());
+            }
+            ["position", count] | ["moves", count] => {
+                let mut read = vec![];
+                for _ in 0..count.parse().unwrap_or(0) {
+                    read.extend(next_line()?);
+                }
+                if words[0] == "moves" {
+                    moves = read;
+                }
+            }
+            ["go", id, _] => {
+                thread::sleep(delay);
+                match moves.choose(&mut rng) {
+                    Some(mv) => writeln!(output, "bestmove {} {}", id, mv)?,
+                    None => writeln!(output, "info no legal moves")?,
+                }
+            }
+            ["quit"] => break,
+            _ => (),
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        agent::{run_agents, RandomAgent},
+        testing::{Nim, NimState},
+    };
+
+    fn reference_engine(config: EngineConfig) -> Result<ExternalAgent, EngineError> {
+        let (engine_input, runner_output) = io::pipe().unwrap();
+        let (runner_input, engine_output) = io::pipe().unwrap();
+        thread::spawn(move || run_reference_engine(BufReader::new(engine_input), engine_output));
+        ExternalAgent::connect(runner_input, runner_output, config)
+    }
+
+    #[test]
+    fn handshake_announces_name_and_options() {
+        let engine = reference_engine(EngineConfig::default()).unwrap();
+        assert_eq!("reference", engine.name());
+        assert_eq!(
+            vec![
+                ("seed".to_owned(), "0".to_owned()),
+                ("delay".to_owned(), "0".to_owned())
+            ],
+            engine.options()
+        );
+
+        let config = EngineConfig {
+            options: vec![("depth".to_owned(), "3".to_owned())],
+            ..EngineConfig::default()
+        };
+        assert!(matches!(
+            reference_engine(config),
+            Err(EngineError::UnknownOption(name)) if name == "depth"
+        ));
+    }
+
+    #[test]
+    fn engine_plays_whole_game() {
+        let config = EngineConfig {
+            options: vec![("seed".to_owned(), "7".to_owned())],
+            ..EngineConfig::default()
+        };
+        let mut agents: Vec<Box<dyn Agent<Nim>>> = vec![
+            Box::new(reference_engine(config).unwrap()),
+            Box::new(RandomAgent::with_seed(7)),
+        ];
+        assert!(run_agents(&Nim(15), &mut agents).is_ok());
+    }
+
+    #[test]
+    fn late_move_is_given_up_and_ignored_later() {
+        let config = EngineConfig {
+            move_time: Duration::from_millis(20),
+            grace: Duration::ZERO,
+            options: vec![("delay".to_owned(), "100".to_owned())],
+            ..EngineConfig::default()
+        };
+        let mut engine = reference_engine(config).unwrap();
+        let state = NimState {
+            stones: 5,
+            player: 0,
+        };
+        assert_eq!(
+            None,
+            Agent::<Nim>::select_move(&mut engine, &state, &[1, 2])
+        );
+        assert!(matches!(engine.last_error(), Some(EngineError::Timeout)));
+
+        // late answer to the first move arrives before answer to this one
+        engine.config.move_time = Duration::from_secs(1);
+        assert_eq!(
+            Some(2),
+            Agent::<Nim>::select_move(&mut engine, &state, &[2])
+        );
+        assert!(engine.last_error().is_none());
+    }
+}
@@ -0,0 +1,246 @@
+use std::time::Instant;
+
+use rand::prelude::*;
+
+use crate::{
+    agent::{Agent, PlayerView},
+    mcts::{apply, current_player, playout, MctsConfig, MoveStats},
+    playered::{self, Outcome, Player, View},
+    prelude::*,
+};
+
+/// Builds full game states that agree with everything a player can see
+pub trait Determinizer<G>
+where
+    G: playered::Game,
+    G::View: View,
+{
+    /// Samples one of states in which `view` could have been shown, filling hidden information randomly
+    fn determinize(&self, view: &PlayerView<G>, rng: &mut dyn RngCore) -> G::State;
+}
+
+/// Single-observer information set Monte Carlo tree search
+///
+/// Every iteration searches a different determinization of player's view, so
+/// agent never reads hidden information. Statistics are shared between
+/// determinizations, and moves not available in all of them are weighted by
+/// how often they were available.
+pub struct Ismcts<D> {
+    config: MctsConfig,
+    determinizer: D,
+    rng: StdRng,
+}
+
+struct Node<M> {
+    mv: Option<M>,
+    /// Player who made move leading to this node
+    player: Option<Player>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    visits: u32,
+    availability: u32,
+    reward: f64,
+}
+
+impl<M> Node<M> {
+    fn new(mv: Option<M>, player: Option<Player>, parent: Option<usize>) -> Self {
+        Node {
+            mv,
+            player,
+            parent,
+            children: vec![],
+            visits: 0,
+            availability: 0,
+            reward: 0.0,
+        }
+    }
+}
+
+impl<D> Ismcts<D> {
+    pub fn with_seed(config: MctsConfig, determinizer: D, seed: u64) -> Ismcts<D> {
+        Ismcts {
+            config,
+            determinizer,
+            rng: SeedableRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn with_random_seed(config: MctsConfig, determinizer: D) -> Ismcts<D> {
+        Ismcts::with_seed(config, determinizer, thread_rng().next_u64())
+    }
+
+    /// Searches from `view` and returns statistics of every move tried in root, most visited first
+    pub fn search<G>(&mut self, view: &PlayerView<G>) -> Vec<MoveStats<G::Move>>
+    where
+        G: playered::Game,
+        G::View: View,
+        G::Move: Clone + PartialEq,
+        G::Result: Outcome,
+        D: Determinizer<G>,
+    {
+        let mut tree: Vec<Node<G::Move>> = vec![Node::new(None, None, None)];
+        let started = Instant::now();
+        let mut iterations = 0;
+
+        while !self.config.budget.spent(iterations, started) {
+            iterations += 1;
+
+            let mut current = self.determinizer.determinize(view, &mut self.rng);
+            let mut node = 0;
+
+            loop {
+                let legal_moves = current.legal_moves();
+                if legal_moves.is_empty() {
+                    break;
+                }
+
+                let untried: Vec<_> = legal_moves
+                    .iter()
+                    .filter(|mv| {
+                        !tree[node]
+                            .children
+                            .iter()
+                            .any(|child| tree[*child].mv.as_ref() == Some(*mv))
+                    })
+                    .collect();
+                let player = current_player::<G>(&current);
+
+                if let Some(mv) = untried.choose(&mut self.rng).map(|mv| (*mv).clone()) {
+                    current = apply::<G>(&current, mv.clone());
+                    tree.push(Node::new(Some(mv), player, Some(node)));
+                    let child = tree.len() - 1;
+                    tree[node].children.push(child);
+                    node = child;
+                    break;
+                }
+
+                let available: Vec<_> = tree[node]
+                    .children
+                    .iter()
+                    .copied()
+                    .filter(|child| {
+                        tree[*child]
+                            .mv
+                            .as_ref()
+                            .is_some_and(|mv| legal_moves.contains(mv))
+                    })
+                    .collect();
+                for child in &available {
+                    tree[*child].availability += 1;
+                }
+                node = self.select_child(&tree, &available);
+                let mv = tree[node].mv.clone().expect("Only root node has no move");
+                current = apply::<G>(&current, mv);
+            }
+
+            let result = playout::<G>(current, &mut self.rng);
+
+            let mut visited = Some(node);
+            while let Some(n) = visited {
+                let node = &mut tree[n];
+                node.visits += 1;
+                node.reward += match (&result, node.player) {
+                    (Some(result), Some(player)) => result.payoff(player),
+                    _ => 0.5,
+                };
+                visited = node.parent;
+            }
+        }
+
+        let mut stats: Vec<_> = tree[0]
+            .children
+            .iter()
+            .map(|child| &tree[*child])
+            .map(|child| MoveStats {
+                mv: child.mv.clone().expect("Child node has a move"),
+                visits: child.visits,
+                win_rate: child.reward / child.visits as f64,
+            })
+            .collect();
+        stats.sort_by_key(|s| std::cmp::Reverse(s.visits));
+        stats
+    }
+
+    fn select_child<M>(&self, tree: &[Node<M>], available: &[usize]) -> usize {
+        let uct = |child: usize| {
+            let node = &tree[child];
+            let visits = node.visits as f64;
+            node.reward / visits
+                + self.config.exploration * ((node.availability as f64).ln() / visits).sqrt()
+        };
+        *available
+            .iter()
+            .max_by(|l, r| uct(**l).total_cmp(&uct(**r)))
+            .expect("Every legal move was tried")
+    }
+}
+
+impl<G, D> Agent<G> for Ismcts<D>
+where
+    G: playered::Game,
+    G::View: View,
+    G::Move: Clone + PartialEq,
+    G::Result: Outcome,
+    D: Determinizer<G>,
+{
+    fn select_move(&mut self, view: &PlayerView<G>, legal_moves: &[G::Move]) -> Option<G::Move> {
+        self.search::<G>(view)
+            .into_iter()
+            .map(|stats| stats.mv)
+            .find(|mv| legal_moves.is_empty() || legal_moves.contains(mv))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mcts::Budget,
+        testing::{Nim, NimState},
+    };
+
+    struct Visible;
+
+    impl Determinizer<Nim> for Visible {
+        fn determinize(&self, view: &NimState, _: &mut dyn RngCore) -> NimState {
+            view.clone()
+        }
+    }
+
+    fn ismcts() -> Ismcts<Visible> {
+        Ismcts::with_seed(
+            MctsConfig {
+                budget: Budget::Iterations(2000),
+                ..Default::default()
+            },
+            Visible,
+            0,
+        )
+    }
+
+    #[test]
+    fn finds_winning_move() {
+        let four = NimState {
+            stones: 4,
+            player: 0,
+        };
+        let five = NimState {
+            stones: 5,
+            player: 1,
+        };
+        assert_eq!(1, ismcts().search::<Nim>(&four)[0].mv);
+        assert_eq!(2, ismcts().search::<Nim>(&five)[0].mv);
+    }
+
+    #[test]
+    fn plays_only_legal_moves_as_agent() {
+        let state = NimState {
+            stones: 4,
+            player: 0,
+        };
+        assert_eq!(
+            Some(2),
+            Agent::<Nim>::select_move(&mut ismcts(), &state, &[2])
+        );
+    }
+}
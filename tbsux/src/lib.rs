@@ -1,5 +1,17 @@
+pub mod agent;
 pub mod cli;
+pub mod clock;
+pub mod conformance;
 pub mod core;
+pub mod engine;
+pub mod ismcts;
+pub mod mcts;
+pub mod negamax;
 pub mod playered;
+pub mod session;
+pub mod tournament;
 
 pub mod prelude;
+
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,319 @@
+use std::time::{Duration, Instant};
+
+use rand::prelude::*;
+
+use crate::{
+    agent::{Agent, PlayerView},
+    playered::{self, Outcome, Player, View},
+    prelude::*,
+};
+
+/// Limits how long a single search runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Budget {
+    Iterations(u32),
+    Time(Duration),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MctsConfig {
+    pub budget: Budget,
+    /// UCT exploration constant, higher values favour less visited moves
+    pub exploration: f64,
+}
+
+impl Default for MctsConfig {
+    fn default() -> MctsConfig {
+        MctsConfig {
+            budget: Budget::Iterations(1000),
+            exploration: std::f64::consts::SQRT_2,
+        }
+    }
+}
+
+/// Search statistics of a single move available in root state
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveStats<M> {
+    pub mv: M,
+    pub visits: u32,
+    /// Average payoff of player making the move
+    pub win_rate: f64,
+}
+
+/// Monte Carlo tree search with UCT selection and random playouts
+///
+/// Sees the whole state of the game, so as an agent it only plays games with
+/// perfect information, where state can be rebuilt from player's view.
+pub struct Mcts {
+    config: MctsConfig,
+    rng: StdRng,
+}
+
+struct Node<M> {
+    mv: Option<M>,
+    /// Player who made move leading to this node
+    player: Option<Player>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<M>,
+    visits: u32,
+    reward: f64,
+}
+
+impl<M> Node<M> {
+    fn new(mv: Option<M>, player: Option<Player>, parent: Option<usize>, untried: Vec<M>) -> Self {
+        Node {
+            mv,
+            player,
+            parent,
+            children: vec![],
+            untried,
+            visits: 0,
+            reward: 0.0,
+        }
+    }
+}
+
+impl Mcts {
+    pub fn with_seed(config: MctsConfig, seed: u64) -> Mcts {
+        Mcts {
+            config,
+            rng: SeedableRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn with_random_seed(config: MctsConfig) -> Mcts {
+        Mcts::with_seed(config, thread_rng().next_u64())
+    }
+
+    /// Searches from `state` and returns statistics of every legal move, most visited first
+    pub fn search<G>(&mut self, state: &G::State) -> Vec<MoveStats<G::Move>>
+    where
+        G: playered::Game,
+        G::View: View,
+        G::State: Clone,
+        G::Move: Clone,
+        G::Result: Outcome,
+    {
+        let mut tree = vec![Node::new(None, None, None, state.legal_moves())];
+        let started = Instant::now();
+        let mut iterations = 0;
+
+        while !self.config.budget.spent(iterations, started) {
+            iterations += 1;
+
+            let mut node = 0;
+            let mut current = state.clone();
+
+            while tree[node].untried.is_empty() && !tree[node].children.is_empty() {
+                node = self.select_child(&tree, node);
+                let mv = tree[node].mv.clone().expect("Only root node has no move");
+                current = apply::<G>(&current, mv);
+            }
+
+            if !tree[node].untried.is_empty() {
+                let untried = &mut tree[node].untried;
+                let mv = untried.swap_remove(self.rng.gen_range(0..untried.len()));
+                let player = current_player::<G>(&current);
+                current = apply::<G>(&current, mv.clone());
+                tree.push(Node::new(
+                    Some(mv),
+                    player,
+                    Some(node),
+                    current.legal_moves(),
+                ));
+                let child = tree.len() - 1;
+                tree[node].children.push(child);
+                node = child;
+            }
+
+            let result = playout::<G>(current, &mut self.rng);
+
+            let mut visited = Some(node);
+            while let Some(n) = visited {
+                let node = &mut tree[n];
+                node.visits += 1;
+                node.reward += match (&result, node.player) {
+                    (Some(result), Some(player)) => result.payoff(player),
+                    _ => 0.5,
+                };
+                visited = node.parent;
+            }
+        }
+
+        let mut stats: Vec<_> = tree[0]
+            .children
+            .iter()
+            .map(|child| &tree[*child])
+            .map(|child| MoveStats {
+                mv: child.mv.clone().expect("Child node has a move"),
+                visits: child.visits,
+                win_rate: child.reward / child.visits as f64,
+            })
+            .collect();
+        stats.sort_by_key(|s| std::cmp::Reverse(s.visits));
+        stats
+    }
+
+    /// Most visited move after search from `state`
+    pub fn best_move<G>(&mut self, state: &G::State) -> Option<G::Move>
+    where
+        G: playered::Game,
+        G::View: View,
+        G::State: Clone,
+        G::Move: Clone,
+        G::Result: Outcome,
+    {
+        self.search::<G>(state)
+            .into_iter()
+            .next()
+            .map(|stats| stats.mv)
+    }
+
+    fn select_child<M>(&self, tree: &[Node<M>], node: usize) -> usize {
+        let parent_visits = (tree[node].visits as f64).ln();
+        let uct = |child: usize| {
+            let visits = tree[child].visits as f64;
+            tree[child].reward / visits + self.config.exploration * (parent_visits / visits).sqrt()
+        };
+        *tree[node]
+            .children
+            .iter()
+            .max_by(|l, r| uct(**l).total_cmp(&uct(**r)))
+            .expect("Node has children")
+    }
+}
+
+impl Budget {
+    pub(crate) fn spent(&self, iterations: u32, started: Instant) -> bool {
+        match self {
+            Budget::Iterations(max) => iterations >= *max,
+            Budget::Time(max) => iterations > 0 && started.elapsed() >= *max,
+        }
+    }
+}
+
+/// Plays random moves until game finishes, `None` if it got stuck without legal moves
+pub(crate) fn playout<G>(mut state: G::State, rng: &mut impl Rng) -> Option<G::Result>
+where
+    G: playered::Game,
+    G::View: View,
+    G::Move: Clone,
+{
+    loop {
+        if let ProgressReport::Finished(result) = state.progress_report() {
+            break Some(result);
+        }
+        let mv = state.legal_moves().choose(rng)?.clone();
+        state = apply::<G>(&state, mv);
+    }
+}
+
+pub(crate) fn current_player<G>(state: &G::State) -> Option<Player>
+where
+    G: playered::Game,
+    G::View: View,
+{
+    match state.progress_report() {
+        ProgressReport::InProgress(view) => Some(view.current_player()),
+        ProgressReport::Finished(_) => None,
+    }
+}
+
+pub(crate) fn apply<G>(state: &G::State, mv: G::Move) -> G::State
+where
+    G: playered::Game,
+    G::View: View,
+{
+    state
+        .move_reducer(mv)
+        .expect("Legal move should be accepted")
+}
+
+impl<G> Agent<G> for Mcts
+where
+    G: playered::Game,
+    G::View: View,
+    G::State: Clone,
+    G::Move: Clone,
+    G::Result: Outcome,
+    PlayerView<G>: Clone + Into<G::State>,
+{
+    fn select_move(&mut self, view: &PlayerView<G>, _: &[G::Move]) -> Option<G::Move> {
+        self.best_move::<G>(&view.clone().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{Nim, NimState};
+
+    fn mcts() -> Mcts {
+        Mcts::with_seed(
+            MctsConfig {
+                budget: Budget::Iterations(2000),
+                ..Default::default()
+            },
+            0,
+        )
+    }
+
+    #[test]
+    fn finds_winning_move() {
+        let four = NimState {
+            stones: 4,
+            player: 0,
+        };
+        let five = NimState {
+            stones: 5,
+            player: 1,
+        };
+        assert_eq!(Some(1), mcts().best_move::<Nim>(&four));
+        assert_eq!(Some(2), mcts().best_move::<Nim>(&five));
+    }
+
+    #[test]
+    fn reports_every_legal_move() {
+        let state = NimState {
+            stones: 7,
+            player: 0,
+        };
+        let stats = mcts().search::<Nim>(&state);
+        assert_eq!(2, stats.len());
+        assert_eq!(2000, stats.iter().map(|s| s.visits).sum::<u32>());
+        assert!(stats[0].visits >= stats[1].visits);
+        assert!(stats[0].win_rate > stats[1].win_rate);
+    }
+
+    #[test]
+    fn respects_time_budget() {
+        let mut mcts = Mcts::with_seed(
+            MctsConfig {
+                budget: Budget::Time(Duration::from_millis(10)),
+                ..Default::default()
+            },
+            0,
+        );
+        let state = NimState {
+            stones: 100,
+            player: 0,
+        };
+        let started = Instant::now();
+        assert!(!mcts.search::<Nim>(&state).is_empty());
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn plays_as_agent() {
+        let mut agent = mcts();
+        let state = NimState {
+            stones: 4,
+            player: 0,
+        };
+        assert_eq!(
+            Some(1),
+            Agent::<Nim>::select_move(&mut agent, &state, &[1, 2])
+        );
+    }
+}
@@ -0,0 +1,394 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    agent::{Agent, PlayerView},
+    mcts::{apply, current_player},
+    playered::{self, Outcome, Player, View},
+    prelude::*,
+};
+
+/// Scores states where search stops before the game ends
+pub trait Evaluator<G>
+where
+    G: Game,
+{
+    /// Value of `state` for `player`, who is about to move in it
+    ///
+    /// Finished games are worth 1.0 for a win, -1.0 for a loss and 0.0 for a tie,
+    /// so values returned here should lie between them.
+    fn evaluate(&self, state: &G::State, player: Player) -> f64;
+}
+
+impl<G, F> Evaluator<G> for F
+where
+    G: Game,
+    F: Fn(&G::State, Player) -> f64,
+{
+    fn evaluate(&self, state: &G::State, player: Player) -> f64 {
+        self(state, player)
+    }
+}
+
+/// Treats every unfinished state as a tie, enough for games small enough to be solved
+pub fn no_evaluation<S>(_: &S, _: Player) -> f64 {
+    0.0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NegamaxConfig {
+    pub max_depth: u32,
+    /// Deepening stops after this time, returning result of last finished depth
+    pub time_limit: Option<Duration>,
+}
+
+impl Default for NegamaxConfig {
+    fn default() -> NegamaxConfig {
+        NegamaxConfig {
+            max_depth: 64,
+            time_limit: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult<M> {
+    pub best_move: Option<M>,
+    /// Value of searched state for player to move
+    pub value: f64,
+    /// Depth of last finished iteration, lower than maximal one when game got solved earlier
+    pub depth: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+struct Entry {
+    depth: u32,
+    /// Whether every searched line reached end of the game, making value valid for any depth
+    complete: bool,
+    value: f64,
+    bound: Bound,
+    /// Index of best move in state's legal moves
+    best_move: Option<usize>,
+}
+
+/// Depth-limited negamax with alpha-beta pruning, iterative deepening and transposition table
+///
+/// Searches two-player zero-sum games, where one player's win is the other one's loss.
+/// Transposition table is keyed by hash of state and kept between searches.
+pub struct Negamax<E> {
+    config: NegamaxConfig,
+    evaluator: E,
+    table: HashMap<u64, Entry>,
+}
+
+/// Signals that time limit was hit in the middle of a search
+struct Timeout;
+
+impl<E> Negamax<E> {
+    pub fn new(config: NegamaxConfig, evaluator: E) -> Negamax<E> {
+        Negamax {
+            config,
+            evaluator,
+            table: HashMap::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.table.clear();
+    }
+
+    pub fn search<G>(&mut self, state: &G::State) -> SearchResult<G::Move>
+    where
+        G: playered::Game,
+        G::View: View,
+        G::State: Hash,
+        G::Move: Clone,
+        G::Result: Outcome,
+        E: Evaluator<G>,
+    {
+        let started = Instant::now();
+        let mut result = SearchResult {
+            best_move: None,
+            value: 0.0,
+            depth: 0,
+        };
+
+        for depth in 1..=self.config.max_depth {
+            let mut search = Search {
+                evaluator: &self.evaluator,
+                table: &mut self.table,
+                deadline: self.config.time_limit.map(|limit| started + limit),
+                cut_off: false,
+            };
+            match search.root::<G>(state, depth) {
+                Ok((best_move, value)) => {
+                    result = SearchResult {
+                        best_move,
+                        value,
+                        depth,
+                    };
+                    if !search.cut_off {
+                        // every line reached end of the game, deeper search changes nothing
+                        break;
+                    }
+                }
+                Err(Timeout) => break,
+            }
+        }
+        result
+    }
+}
+
+struct Search<'a, E> {
+    evaluator: &'a E,
+    table: &'a mut HashMap<u64, Entry>,
+    deadline: Option<Instant>,
+    /// Whether any line was cut off by depth limit instead of reaching end of the game
+    cut_off: bool,
+}
+
+impl<E> Search<'_, E> {
+    fn root<G>(&mut self, state: &G::State, depth: u32) -> Result<(Option<G::Move>, f64), Timeout>
+    where
+        G: playered::Game,
+        G::View: View,
+        G::State: Hash,
+        G::Move: Clone,
+        G::Result: Outcome,
+        E: Evaluator<G>,
+    {
+        let (value, best_move) = self.negamax::<G>(state, depth, -f64::INFINITY, f64::INFINITY)?;
+        let best_move = best_move.and_then(|index| state.legal_moves().get(index).cloned());
+        // negating ties produces -0.0, adding zero normalizes it
+        Ok((best_move, value + 0.0))
+    }
+
+    /// Value of `state` for player to move in it, with index of best move among legal moves
+    fn negamax<G>(
+        &mut self,
+        state: &G::State,
+        depth: u32,
+        mut alpha: f64,
+        beta: f64,
+    ) -> Result<(f64, Option<usize>), Timeout>
+    where
+        G: playered::Game,
+        G::View: View,
+        G::State: Hash,
+        G::Move: Clone,
+        G::Result: Outcome,
+        E: Evaluator<G>,
+    {
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(Timeout);
+        }
+
+        let player = match current_player::<G>(state) {
+            Some(player) => player,
+            None => return Ok((0.0, None)),
+        };
+        let moves = state.legal_moves();
+        if depth == 0 || moves.is_empty() {
+            self.cut_off = true;
+            return Ok((self.evaluator.evaluate(state, player), None));
+        }
+
+        let key = hash(state);
+        let original_alpha = alpha;
+        let mut first_move = None;
+        if let Some(entry) = self.table.get(&key) {
+            first_move = entry.best_move;
+            let usable = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => entry.value >= beta,
+                Bound::Upper => entry.value <= alpha,
+            };
+            if usable && (entry.complete || entry.depth >= depth) {
+                self.cut_off |= !entry.complete;
+                return Ok((entry.value, entry.best_move));
+            }
+        }
+
+        let outer_cut_off = std::mem::replace(&mut self.cut_off, false);
+
+        let mut order: Vec<usize> = (0..moves.len()).collect();
+        if let Some(first) = first_move.filter(|first| *first < moves.len()) {
+            order.swap(0, first);
+        }
+
+        let mut best = -f64::INFINITY;
+        let mut best_move = None;
+        for index in order {
+            let child = apply::<G>(state, moves[index].clone());
+            let value = match child.progress_report() {
+                ProgressReport::Finished(result) => 2.0 * result.payoff(player) - 1.0,
+                ProgressReport::InProgress(view) if view.current_player() == player => {
+                    self.negamax::<G>(&child, depth - 1, alpha, beta)?.0
+                }
+                ProgressReport::InProgress(_) => {
+                    -self.negamax::<G>(&child, depth - 1, -beta, -alpha)?.0
+                }
+            };
+            if value > best {
+                best = value;
+                best_move = Some(index);
+            }
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best <= original_alpha {
+            Bound::Upper
+        } else if best >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        let complete = !self.cut_off;
+        self.cut_off |= outer_cut_off;
+        self.table.insert(
+            key,
+            Entry {
+                depth,
+                complete,
+                value: best,
+                bound,
+                best_move,
+            },
+        );
+        Ok((best, best_move))
+    }
+}
+
+fn hash<S: Hash>(state: &S) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<G, E> Agent<G> for Negamax<E>
+where
+    G: playered::Game,
+    G::View: View,
+    G::State: Hash,
+    G::Move: Clone,
+    G::Result: Outcome,
+    E: Evaluator<G>,
+    PlayerView<G>: Clone + Into<G::State>,
+{
+    /// Plays first legal move when time ran out before first depth was searched
+    fn select_move(&mut self, view: &PlayerView<G>, legal_moves: &[G::Move]) -> Option<G::Move> {
+        self.search::<G>(&view.clone().into())
+            .best_move
+            .or_else(|| legal_moves.first().cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{Nim, NimState};
+
+    fn nim(stones: u32) -> NimState {
+        NimState { stones, player: 0 }
+    }
+
+    #[test]
+    fn solves_winning_position() {
+        let mut negamax = Negamax::new(NegamaxConfig::default(), no_evaluation);
+        let result = negamax.search::<Nim>(&nim(7));
+        assert_eq!(Some(1), result.best_move);
+        assert_eq!(1.0, result.value);
+    }
+
+    #[test]
+    fn solves_losing_position() {
+        let mut negamax = Negamax::new(NegamaxConfig::default(), no_evaluation);
+        assert_eq!(-1.0, negamax.search::<Nim>(&nim(9)).value);
+    }
+
+    #[test]
+    fn stops_deepening_once_game_is_solved() {
+        let mut negamax = Negamax::new(NegamaxConfig::default(), no_evaluation);
+        assert_eq!(5, negamax.search::<Nim>(&nim(5)).depth);
+    }
+
+    #[test]
+    fn evaluates_states_at_depth_limit() {
+        let config = NegamaxConfig {
+            max_depth: 1,
+            time_limit: None,
+        };
+        // moving with even number of stones left is considered bad
+        let evaluator = |state: &NimState, _: Player| {
+            if state.stones.is_multiple_of(2) {
+                -0.5
+            } else {
+                0.5
+            }
+        };
+        let result = Negamax::new(config, evaluator).search::<Nim>(&nim(20));
+        assert_eq!(1, result.depth);
+        assert_eq!(0.5, result.value);
+        assert_eq!(Some(2), result.best_move);
+    }
+
+    #[test]
+    fn reuses_transposition_table_between_searches() {
+        let mut negamax = Negamax::new(NegamaxConfig::default(), no_evaluation);
+        negamax.search::<Nim>(&nim(31));
+        assert!(!negamax.table.is_empty());
+        let result = negamax.search::<Nim>(&nim(31));
+        assert_eq!(Some(1), result.best_move);
+        assert_eq!(1.0, result.value);
+        negamax.clear();
+        assert!(negamax.table.is_empty());
+    }
+
+    #[test]
+    fn returns_nothing_when_time_runs_out_before_first_depth() {
+        let config = NegamaxConfig {
+            max_depth: 64,
+            time_limit: Some(Duration::from_secs(0)),
+        };
+        let result = Negamax::new(config, no_evaluation).search::<Nim>(&nim(30));
+        assert_eq!(None, result.best_move);
+        assert_eq!(0, result.depth);
+    }
+
+    #[test]
+    fn plays_first_legal_move_when_time_runs_out() {
+        let config = NegamaxConfig {
+            max_depth: 64,
+            time_limit: Some(Duration::from_secs(0)),
+        };
+        let mut negamax = Negamax::new(config, no_evaluation);
+        assert_eq!(
+            Some(1),
+            Agent::<Nim>::select_move(&mut negamax, &nim(30), &[1, 2])
+        );
+    }
+
+    #[test]
+    fn plays_as_agent() {
+        let mut negamax = Negamax::new(NegamaxConfig::default(), no_evaluation);
+        assert_eq!(
+            Some(2),
+            Agent::<Nim>::select_move(&mut negamax, &nim(5), &[1, 2])
+        );
+    }
+}
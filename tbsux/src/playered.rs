@@ -15,3 +15,13 @@ pub trait View {
     fn current_player(&self) -> Player;
     fn player_view(&self, player: Player) -> Self::PlayerView;
 }
+
+pub trait Outcome {
+    /// Reward of `player` for finished game, from 0.0 for a loss to 1.0 for a win
+    fn payoff(&self, player: Player) -> f64;
+
+    /// Points scored by `player`, used only in statistics
+    fn points(&self, player: Player) -> f64 {
+        self.payoff(player)
+    }
+}
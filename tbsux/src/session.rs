@@ -0,0 +1,127 @@
+use crate::prelude::*;
+
+/// Game in progress together with every state it went through
+///
+/// States are kept after undo, so they can be restored with redo until a new move is played.
+pub struct Session<G>
+where
+    G: Game,
+{
+    states: Vec<G::State>,
+    ply: usize,
+}
+
+impl<G> Session<G>
+where
+    G: Game,
+{
+    pub fn new(state: G::State) -> Session<G> {
+        Session {
+            states: vec![state],
+            ply: 0,
+        }
+    }
+
+    pub fn current(&self) -> &G::State {
+        &self.states[self.ply]
+    }
+
+    /// Number of moves played to reach current state
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// Number of moves played to reach latest state, including undone ones
+    pub fn last_ply(&self) -> usize {
+        self.states.len() - 1
+    }
+
+    /// State after `ply` moves, if it was reached
+    pub fn state_at(&self, ply: usize) -> Option<&G::State> {
+        self.states.get(ply)
+    }
+
+    /// Plays a move in current state, dropping all undone states
+    pub fn play(&mut self, mv: G::Move) -> Result<(), G::Error> {
+        let state = self.current().move_reducer(mv)?;
+        self.states.truncate(self.ply + 1);
+        self.states.push(state);
+        self.ply += 1;
+        Ok(())
+    }
+
+    /// Goes back by one move, returns `false` if there is nothing to undo
+    pub fn undo(&mut self) -> bool {
+        self.jump_to(self.ply.wrapping_sub(1))
+    }
+
+    /// Restores last undone move, returns `false` if there is nothing to redo
+    pub fn redo(&mut self) -> bool {
+        self.jump_to(self.ply + 1)
+    }
+
+    /// Moves to state after `ply` moves, returns `false` if it was never reached
+    pub fn jump_to(&mut self, ply: usize) -> bool {
+        if ply < self.states.len() {
+            self.ply = ply;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{Nim, NimError};
+
+    fn session() -> Session<Nim> {
+        let mut session = Session::new(Nim(10).initial_state());
+        session.play(1).unwrap();
+        session.play(2).unwrap();
+        session
+    }
+
+    #[test]
+    fn undo_and_redo_move_between_states() {
+        let mut session = session();
+        assert_eq!(7, session.current().stones);
+        assert!(session.undo());
+        assert_eq!(9, session.current().stones);
+        assert!(session.undo());
+        assert!(!session.undo());
+        assert_eq!(0, session.ply());
+        assert!(session.redo());
+        assert!(session.redo());
+        assert!(!session.redo());
+        assert_eq!(7, session.current().stones);
+    }
+
+    #[test]
+    fn playing_after_undo_drops_undone_moves() {
+        let mut session = session();
+        session.undo();
+        session.play(1).unwrap();
+        assert_eq!(8, session.current().stones);
+        assert_eq!(2, session.last_ply());
+        assert!(!session.redo());
+    }
+
+    #[test]
+    fn jumps_only_to_reached_plies() {
+        let mut session = session();
+        assert!(session.jump_to(0));
+        assert_eq!(10, session.current().stones);
+        assert_eq!(2, session.last_ply());
+        assert!(!session.jump_to(3));
+        assert_eq!(0, session.ply());
+    }
+
+    #[test]
+    fn illegal_move_keeps_session_unchanged() {
+        let mut session = session();
+        assert!(matches!(session.play(3), Err(NimError)));
+        assert_eq!(2, session.ply());
+    }
+}
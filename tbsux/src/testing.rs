@@ -0,0 +1,105 @@
+use std::{error::Error, fmt};
+
+use crate::{
+    playered::{self, Outcome, Player, View},
+    prelude::*,
+};
+
+/// Players take turns taking one or two stones, the one who takes the last stone wins
+pub struct Nim(pub u32);
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct NimWinner(pub Player);
+
+#[derive(Debug)]
+pub struct NimError;
+
+impl fmt::Display for NimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Error for NimError {}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NimState {
+    pub stones: u32,
+    pub player: Player,
+}
+
+impl fmt::Display for NimState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} stones, player {} to move", self.stones, self.player)
+    }
+}
+
+pub struct NimView(pub NimState);
+
+impl View for NimView {
+    type PlayerView = NimState;
+
+    fn current_player(&self) -> Player {
+        self.0.player
+    }
+
+    fn player_view(&self, _: Player) -> NimState {
+        self.0.clone()
+    }
+}
+
+impl Game for Nim {
+    type State = NimState;
+    type Move = u32;
+    type Result = NimWinner;
+    type View = NimView;
+    type Error = NimError;
+
+    fn initial_state(&self) -> NimState {
+        NimState {
+            stones: self.0,
+            player: 0,
+        }
+    }
+}
+
+impl playered::Game for Nim {
+    fn no_of_players(&self) -> u32 {
+        2
+    }
+}
+
+impl State<Nim> for NimState {
+    fn progress_report(&self) -> ProgressReport<Nim> {
+        if self.stones == 0 {
+            ProgressReport::Finished(NimWinner(1 - self.player))
+        } else {
+            ProgressReport::InProgress(NimView(self.clone()))
+        }
+    }
+
+    fn move_reducer(&self, mv: u32) -> Result<NimState, NimError> {
+        if mv == 0 || mv > 2 || mv > self.stones {
+            Err(NimError)
+        } else {
+            Ok(NimState {
+                stones: self.stones - mv,
+                player: 1 - self.player,
+            })
+        }
+    }
+
+    fn legal_moves(&self) -> Vec<u32> {
+        (1..=self.stones.min(2)).collect()
+    }
+}
+
+impl Outcome for NimWinner {
+    fn payoff(&self, player: Player) -> f64 {
+        if self.0 == player {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
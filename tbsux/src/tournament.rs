@@ -0,0 +1,270 @@
+use std::fmt;
+
+use crate::{
+    agent::{play_turn, Agent, RunnerError},
+    playered::{self, Outcome, Player, View},
+    prelude::*,
+};
+
+/// Creates agent for a single game from given seed
+pub type AgentFactory<G> = Box<dyn Fn(u64) -> Box<dyn Agent<G>>>;
+
+pub struct Entrant<G>
+where
+    G: playered::Game,
+    G::View: View,
+{
+    pub name: String,
+    pub agent: AgentFactory<G>,
+}
+
+impl<G> Entrant<G>
+where
+    G: playered::Game,
+    G::View: View,
+{
+    pub fn new(name: &str, agent: impl Fn(u64) -> Box<dyn Agent<G>> + 'static) -> Entrant<G> {
+        Entrant {
+            name: name.to_owned(),
+            agent: Box::new(agent),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TournamentConfig {
+    /// Number of games played by every lineup, each one with a different seed
+    pub games_per_lineup: u32,
+    pub initial_rating: f64,
+    /// Maximal change of Elo rating after a single game
+    pub k_factor: f64,
+}
+
+impl Default for TournamentConfig {
+    fn default() -> TournamentConfig {
+        TournamentConfig {
+            games_per_lineup: 10,
+            initial_rating: 1500.0,
+            k_factor: 16.0,
+        }
+    }
+}
+
+/// Results of a single entrant
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub games: u32,
+    /// Average payoff, where ties count as half of a win
+    pub win_rate: f64,
+    pub average_points: f64,
+    pub rating: f64,
+}
+
+/// Standings of every entrant, best rated first
+#[derive(Debug, Clone, PartialEq)]
+pub struct TournamentReport {
+    pub standings: Vec<Standing>,
+}
+
+/// Plays every lineup of entrants for configured number of games and rates them
+///
+/// Lineups are all orderings of entrants - or of every choice of them, when
+/// there are more entrants than seats - repeated around the table when there
+/// are more seats than entrants. Every lineup plays games created by `new_game`
+/// from the same seeds, so all entrants get every seat in the same deals.
+/// Elo ratings are updated after every game, comparing payoffs of each pair of entrants.
+pub fn run_tournament<G>(
+    new_game: impl Fn(u64) -> G,
+    entrants: &[Entrant<G>],
+    config: TournamentConfig,
+) -> Result<TournamentReport, RunnerError<G::Error>>
+where
+    G: playered::Game,
+    G::View: View,
+    G::Result: Outcome,
+{
+    let mut standings: Vec<_> = entrants
+        .iter()
+        .map(|entrant| Standing {
+            name: entrant.name.clone(),
+            games: 0,
+            win_rate: 0.0,
+            average_points: 0.0,
+            rating: config.initial_rating,
+        })
+        .collect();
+    let mut payoffs = vec![0.0; entrants.len()];
+    let mut points = vec![0.0; entrants.len()];
+
+    for seed in 0..config.games_per_lineup as u64 {
+        let seats = new_game(seed).no_of_players() as usize;
+        for lineup in lineups(entrants.len(), seats) {
+            let game = new_game(seed);
+            let seating: Vec<usize> = (0..seats).map(|seat| lineup[seat % lineup.len()]).collect();
+            let mut agents: Vec<_> = seating
+                .iter()
+                .enumerate()
+                .map(|(seat, entrant)| {
+                    (entrants[*entrant].agent)(seed * seats as u64 + seat as u64)
+                })
+                .collect();
+
+            let mut state = game.initial_state();
+            let result = loop {
+                state = match state.progress_report() {
+                    ProgressReport::Finished(result) => break result,
+                    ProgressReport::InProgress(view) => play_turn(&state, &view, &mut agents)?,
+                };
+            };
+
+            // entrant sitting at many seats gets average of their results
+            let average = |entrant: usize, value: &dyn Fn(Player) -> f64| {
+                let own: Vec<_> = (0..seats)
+                    .filter(|seat| seating[*seat] == entrant)
+                    .collect();
+                own.iter().map(|seat| value(*seat as Player)).sum::<f64>() / own.len() as f64
+            };
+            let game_payoffs: Vec<_> = lineup
+                .iter()
+                .map(|entrant| average(*entrant, &|player| result.payoff(player)))
+                .collect();
+            for (index, entrant) in lineup.iter().enumerate() {
+                standings[*entrant].games += 1;
+                payoffs[*entrant] += game_payoffs[index];
+                points[*entrant] += average(*entrant, &|player| result.points(player));
+            }
+            update_ratings(&mut standings, &lineup, &game_payoffs, config.k_factor);
+        }
+    }
+
+    for (index, standing) in standings.iter_mut().enumerate() {
+        if standing.games > 0 {
+            standing.win_rate = payoffs[index] / standing.games as f64;
+            standing.average_points = points[index] / standing.games as f64;
+        }
+    }
+    standings.sort_by(|l, r| r.rating.total_cmp(&l.rating));
+    Ok(TournamentReport { standings })
+}
+
+/// Every ordering of `min(entrants, seats)` distinct entrants
+fn lineups(entrants: usize, seats: usize) -> Vec<Vec<usize>> {
+    let length = entrants.min(seats);
+    let mut lineups = vec![vec![]];
+    for _ in 0..length {
+        lineups = lineups
+            .into_iter()
+            .flat_map(|lineup: Vec<usize>| {
+                (0..entrants)
+                    .filter(|entrant| !lineup.contains(entrant))
+                    .map(|entrant| [lineup.clone(), vec![entrant]].concat())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+    }
+    lineups
+}
+
+fn update_ratings(standings: &mut [Standing], lineup: &[usize], payoffs: &[f64], k_factor: f64) {
+    let ratings: Vec<_> = lineup
+        .iter()
+        .map(|entrant| standings[*entrant].rating)
+        .collect();
+    let opponents = (lineup.len() - 1).max(1) as f64;
+    for (index, entrant) in lineup.iter().enumerate() {
+        let change: f64 = (0..lineup.len())
+            .filter(|other| *other != index)
+            .map(|other| {
+                let expected = 1.0 / (1.0 + 10f64.powf((ratings[other] - ratings[index]) / 400.0));
+                let actual = match payoffs[index].total_cmp(&payoffs[other]) {
+                    std::cmp::Ordering::Greater => 1.0,
+                    std::cmp::Ordering::Equal => 0.5,
+                    std::cmp::Ordering::Less => 0.0,
+                };
+                actual - expected
+            })
+            .sum();
+        standings[*entrant].rating += k_factor * change / opponents;
+    }
+}
+
+impl fmt::Display for TournamentReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<20} {:>6} {:>8} {:>8} {:>8}",
+            "NAME", "GAMES", "WIN %", "POINTS", "ELO"
+        )?;
+        for standing in &self.standings {
+            writeln!(
+                f,
+                "{:<20} {:>6} {:>8.1} {:>8.2} {:>8.0}",
+                standing.name,
+                standing.games,
+                standing.win_rate * 100.0,
+                standing.average_points,
+                standing.rating
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        agent::RandomAgent,
+        testing::{Nim, NimState},
+    };
+
+    /// Leaves opponent a multiple of three stones whenever it can
+    struct Perfect;
+
+    impl Agent<Nim> for Perfect {
+        fn select_move(&mut self, view: &NimState, _: &[u32]) -> Option<u32> {
+            Some((view.stones % 3).max(1))
+        }
+    }
+
+    fn entrants() -> Vec<Entrant<Nim>> {
+        vec![
+            Entrant::new("random", |seed| Box::new(RandomAgent::with_seed(seed))),
+            Entrant::new("perfect", |_| Box::new(Perfect)),
+        ]
+    }
+
+    #[test]
+    fn lineups_cover_every_seating() {
+        assert_eq!(vec![vec![0, 1], vec![1, 0]], lineups(2, 4));
+        assert_eq!(6, lineups(3, 3).len());
+        assert_eq!(12, lineups(4, 2).len());
+    }
+
+    #[test]
+    fn better_agent_wins_more_and_gets_higher_rating() {
+        let report = run_tournament(|_| Nim(10), &entrants(), TournamentConfig::default()).unwrap();
+        let perfect = &report.standings[0];
+        let random = &report.standings[1];
+        assert_eq!("perfect", perfect.name);
+        assert_eq!(20, perfect.games);
+        assert!(perfect.win_rate > 0.9);
+        assert!((perfect.win_rate + random.win_rate - 1.0).abs() < 1e-9);
+        assert!(perfect.rating > 1500.0 && random.rating < 1500.0);
+    }
+
+    #[test]
+    fn equal_agents_keep_their_ratings() {
+        let entrants = vec![
+            Entrant::new("first", |_| Box::new(Perfect) as Box<dyn Agent<Nim>>),
+            Entrant::new("second", |_| Box::new(Perfect)),
+        ];
+        // seats are swapped between lineups, so both win equally often
+        let report = run_tournament(|_| Nim(10), &entrants, TournamentConfig::default()).unwrap();
+        for standing in report.standings {
+            assert_eq!(0.5, standing.win_rate);
+            assert!((standing.rating - 1500.0).abs() < 20.0);
+        }
+    }
+}